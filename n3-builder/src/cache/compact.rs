@@ -0,0 +1,284 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use n3_program::compact::{
+    Compact, CompactContext, Decompact, DecompactContext, Graphs, Table, Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::ast;
+use crate::externs::{ExternIR, ExternIRShapes};
+use crate::graph::{Graph, RefGraph};
+use crate::nodes::NodeIR;
+use crate::tensor::{IRData, TensorNode};
+
+/// A built node which can be stored in the persistent cache.
+#[derive(Serialize, Deserialize)]
+pub struct CompactedNode {
+    graphs: Graphs<Table>,
+    node: CompactedTensorNode,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CompactedTensorNode {
+    Node(CompactedNodeIR),
+    Extern(CompactedExternIR),
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompactedIRData {
+    id: u64,
+    name: String,
+    graph: u64,
+    input: ast::Outs,
+    output: ast::Outs,
+    shared: Option<String>,
+    // note: the constraints are not a part of the program
+    constraints: BTreeMap<String, ast::LetConstraint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompactedNodeIR {
+    data: CompactedIRData,
+    ty: ast::LetNodeType,
+    tensor_graph: Vec<CompactedTensorNode>,
+    repeat: Option<Value>,
+    guard: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompactedExternIR {
+    ty: ast::ExternNodeType,
+    data: CompactedIRData,
+    input: Option<CompactedShapes>,
+    output: Option<CompactedShapes>,
+}
+
+type CompactedShapes = BTreeMap<String, Option<Vec<Value>>>;
+
+impl CompactedNode {
+    /// Compacts the node, or returns `None` if the node cannot be stored.
+    ///
+    /// The exec nodes, the generic nodes and the nodes referring outer variables are not stored.
+    pub fn compact(node: &TensorNode) -> Option<Self> {
+        let mut compactor = Compactor {
+            ctx: CompactContext::new(Default::default()),
+            refs: BTreeSet::new(),
+        };
+        let node = compactor.node(node)?;
+
+        let graphs = compactor.ctx.into_graphs();
+        if compactor.refs.iter().all(|id| graphs.contains_key(id)) {
+            Some(Self { graphs, node })
+        } else {
+            None
+        }
+    }
+
+    pub fn decompact(self) -> TensorNode {
+        let mut ctx = DecompactContext::default();
+
+        // note: ordered (graphs -> variables)
+        let mut values = BTreeMap::new();
+        for (id, table) in self.graphs.0 {
+            let (graph, graph_values) = table.decompact(&mut ctx, id);
+            ctx.insert_graph(id, graph);
+            values.insert(id, graph_values);
+        }
+        ctx.apply_variables(Graphs(values));
+
+        let mut decompactor = Decompactor {
+            ctx,
+            graphs: HashMap::new(),
+        };
+        decompactor.node(self.node)
+    }
+}
+
+struct Compactor {
+    ctx: CompactContext,
+    refs: BTreeSet<u64>,
+}
+
+impl Compactor {
+    fn node(&mut self, node: &TensorNode) -> Option<CompactedTensorNode> {
+        match node {
+            TensorNode::Node(node) => {
+                if node.template.is_some() {
+                    return None;
+                }
+
+                Some(CompactedTensorNode::Node(CompactedNodeIR {
+                    data: self.data(&node.data)?,
+                    ty: node.ty,
+                    tensor_graph: node
+                        .tensor_graph
+                        .iter()
+                        .map(|x| self.node(x))
+                        .collect::<Option<_>>()?,
+                    repeat: match &node.repeat {
+                        Some(repeat) => Some(self.value(repeat)?),
+                        None => None,
+                    },
+                    guard: match &node.guard {
+                        Some(guard) => Some(self.value(guard)?),
+                        None => None,
+                    },
+                }))
+            }
+            TensorNode::Extern(node) => Some(CompactedTensorNode::Extern(CompactedExternIR {
+                ty: node.ty,
+                data: self.data(&node.data)?,
+                input: self.shapes(&node.shapes.input)?,
+                output: self.shapes(&node.shapes.output)?,
+            })),
+            TensorNode::Exec(_) => None,
+        }
+    }
+
+    fn data(&mut self, data: &IRData) -> Option<CompactedIRData> {
+        let graph = data.graph.borrow();
+        for var in graph.variables().values() {
+            if let Some(value) = &var.borrow().value {
+                self.test_value(value)?;
+            }
+        }
+
+        Some(CompactedIRData {
+            id: data.id,
+            name: data.name.clone(),
+            graph: graph.to_table().compact(&mut self.ctx),
+            input: data.input.clone(),
+            output: data.output.clone(),
+            shared: data.shared.clone(),
+            constraints: graph
+                .variables()
+                .iter()
+                .filter_map(|(name, var)| Some((name.clone(), var.borrow().constraint.clone()?)))
+                .collect(),
+        })
+    }
+
+    fn shapes(&mut self, shapes: &Option<ast::Shapes>) -> Option<Option<CompactedShapes>> {
+        let shapes = match shapes {
+            Some(shapes) => shapes,
+            None => return Some(None),
+        };
+
+        shapes
+            .0
+            .borrow()
+            .iter()
+            .map(|(name, shape)| {
+                let shape = match shape {
+                    Some(shape) => Some(
+                        shape
+                            .0
+                            .iter()
+                            .map(|x| self.value(x))
+                            .collect::<Option<_>>()?,
+                    ),
+                    None => None,
+                };
+                Some((name.clone(), shape))
+            })
+            .collect::<Option<_>>()
+            .map(Some)
+    }
+
+    fn value(&mut self, value: &ast::Value) -> Option<Value> {
+        self.test_value(value)?;
+        Some(value.compact(&mut self.ctx))
+    }
+
+    /// Collects the referred graphs, and tests whether the value has no nodes.
+    fn test_value(&mut self, value: &ast::Value) -> Option<()> {
+        match value {
+            ast::Value::Node(_) => None,
+            ast::Value::Variable(var) => {
+                self.refs.insert(var.borrow().id?);
+                Some(())
+            }
+            ast::Value::Expr(expr) => {
+                self.test_value(&expr.lhs)?;
+                match &expr.rhs {
+                    Some(rhs) => self.test_value(rhs),
+                    None => Some(()),
+                }
+            }
+            ast::Value::List(list) => list.iter().try_for_each(|x| self.test_value(x)),
+            ast::Value::Map(map) => map.values().flatten().try_for_each(|x| self.test_value(x)),
+            _ => Some(()),
+        }
+    }
+}
+
+struct Decompactor {
+    ctx: DecompactContext,
+    graphs: HashMap<u64, RefGraph>,
+}
+
+impl Decompactor {
+    fn node(&mut self, node: CompactedTensorNode) -> TensorNode {
+        match node {
+            CompactedTensorNode::Node(node) => NodeIR {
+                data: self.data(node.data),
+                ty: node.ty,
+                tensor_graph: node
+                    .tensor_graph
+                    .into_iter()
+                    .map(|x| self.node(x))
+                    .collect::<Vec<_>>()
+                    .into(),
+                repeat: node.repeat.map(|x| x.decompact(&mut self.ctx, ())),
+                guard: node.guard.map(|x| x.decompact(&mut self.ctx, ())),
+                template: None,
+            }
+            .into(),
+            CompactedTensorNode::Extern(node) => ExternIR {
+                ty: node.ty,
+                data: self.data(node.data),
+                shapes: ExternIRShapes {
+                    input: node.input.map(|x| self.shapes(x)),
+                    output: node.output.map(|x| self.shapes(x)),
+                },
+            }
+            .into(),
+        }
+    }
+
+    fn data(&mut self, data: CompactedIRData) -> IRData {
+        // note: the graphs with the same id should be shared
+        let ctx = &self.ctx;
+        let graph = self
+            .graphs
+            .entry(data.graph)
+            .or_insert_with(|| Graph::from_table(ctx.get_graph(data.graph).clone()).into())
+            .clone();
+        for (name, constraint) in data.constraints {
+            if let Some(var) = graph.borrow().variables().get(&name) {
+                var.borrow_mut().constraint = Some(constraint);
+            }
+        }
+
+        IRData {
+            id: data.id,
+            name: data.name,
+            graph,
+            input: data.input,
+            output: data.output,
+            shared: data.shared,
+        }
+    }
+
+    fn shapes(&mut self, shapes: CompactedShapes) -> ast::Shapes {
+        ast::Shapes::new(
+            shapes
+                .into_iter()
+                .map(|(name, shape)| {
+                    let shape = shape.map(|x| ast::Shape(x.decompact(&mut self.ctx, ())));
+                    (name, shape)
+                })
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+// distinguishes the temporary files written at the same time
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A content-addressed cache of the built nodes, which persists between the runs.
+///
+/// Each entry is keyed by the hash of its source,
+/// and is valid only while the hashes of its dependencies are not changed.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskCacheEntry {
+    dependencies: BTreeMap<String, u64>,
+    binary: Vec<u8>,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn load(&self, name: &str, hash: u64) -> Option<(BTreeMap<String, u64>, Vec<u8>)> {
+        let binary = fs::read(self.path(name, hash)).ok()?;
+        let entry: DiskCacheEntry = bincode::deserialize(&binary).ok()?;
+        Some((entry.dependencies, entry.binary))
+    }
+
+    pub fn save(
+        &self,
+        name: &str,
+        hash: u64,
+        dependencies: BTreeMap<String, u64>,
+        binary: Vec<u8>,
+    ) -> Result<()> {
+        let entry = DiskCacheEntry {
+            dependencies,
+            binary,
+        };
+        let binary = bincode::serialize(&entry).map_err(n3_program::error::Error::from)?;
+
+        fs::create_dir_all(&self.dir)?;
+
+        // note: the entry is renamed into place, so the readers never see a partial file
+        let path = self.path(name, hash);
+        let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_path = path.with_extension(format!("{}-{}.tmp", process::id(), count));
+        fs::write(&temp_path, binary)?;
+        fs::rename(&temp_path, &path).map_err(|e| {
+            fs::remove_file(&temp_path).ok();
+            e.into()
+        })
+    }
+
+    fn path(&self, name: &str, hash: u64) -> PathBuf {
+        self.dir.join(format!("{}-{:016x}.bin", name, hash))
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+// the hashes of the sources of the dependencies
+type Dependencies = BTreeMap<String, u64>;
+
+/// The compacted nodes shared between the roots spawned from the same root,
+/// so that each of them doesn't rebuild the same sources.
+///
+/// Like the `DiskCache`, each entry is keyed by the hash of its source.
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: RwLock<HashMap<(String, u64), MemoryCacheEntry>>,
+}
+
+#[derive(Debug)]
+struct MemoryCacheEntry {
+    dependencies: Dependencies,
+    binary: Arc<Vec<u8>>,
+}
+
+impl MemoryCache {
+    pub fn load(&self, name: &str, hash: u64) -> Option<(Dependencies, Arc<Vec<u8>>)> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries.get(&(name.to_string(), hash))?;
+        Some((entry.dependencies.clone(), entry.binary.clone()))
+    }
+
+    pub fn save(&self, name: &str, hash: u64, dependencies: Dependencies, binary: Arc<Vec<u8>>) {
+        let entry = MemoryCacheEntry {
+            dependencies,
+            binary,
+        };
+        self.entries
+            .write()
+            .unwrap()
+            .insert((name.to_string(), hash), entry);
+    }
+}
@@ -0,0 +1,487 @@
+mod compact;
+mod disk;
+mod memory;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use crate::context::{Build, CloneSafe};
+use crate::error::{Result, TensorNodeError};
+use crate::nodes::NodeRoot;
+
+pub use self::compact::CompactedNode;
+pub use self::disk::DiskCache;
+pub use self::memory::MemoryCache;
+
+/// The immutable sources of the nodes, which can be shared between the threads.
+///
+/// A node can be defined in several roots, and the path with the highest priority is used.
+#[derive(Debug, Default)]
+pub struct NodeSources {
+    // name -> priority -> path
+    paths: RwLock<HashMap<String, BTreeMap<usize, String>>>,
+    sources: RwLock<HashMap<String, String>>,
+}
+
+/// Detects whether the source of a node has been changed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stamp {
+    Modified { time: SystemTime, len: u64 },
+    Hash(u64),
+}
+
+impl NodeSources {
+    /// The priority of the standard library, which can be shadowed by any other roots.
+    pub const PRIORITY_STD: usize = 0;
+    /// The priority of the nodes added explicitly, which shadow any other roots.
+    pub const PRIORITY_LOCAL: usize = usize::MAX;
+
+    pub fn with_paths(paths: HashMap<String, String>) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|(name, path)| (name, Some((Self::PRIORITY_STD, path)).into_iter().collect()))
+            .collect();
+        Self {
+            paths: RwLock::new(paths),
+            sources: RwLock::default(),
+        }
+    }
+
+    pub fn add_source(&self, name: String, source: String) {
+        self.sources.write().unwrap().insert(name, source);
+    }
+
+    pub fn add_path(&self, name: String, path: String) {
+        self.add_path_with_priority(name, path, Self::PRIORITY_LOCAL);
+    }
+
+    pub fn add_path_with_priority(&self, name: String, path: String, priority: usize) {
+        let mut paths = self.paths.write().unwrap();
+        let paths = paths.entry(name).or_default();
+
+        // note: the same file may be found in several roots
+        let old = paths.iter().find(|(_, x)| **x == path).map(|(&k, _)| k);
+        if let Some(old) = old {
+            if old >= priority {
+                return;
+            }
+            paths.remove(&old);
+        }
+        paths.insert(priority, path);
+    }
+
+    /// Forgets the paths of the files which have been removed.
+    ///
+    /// The shadowed paths are used again if the files shadowing them have been removed.
+    pub fn remove_missing_paths(&self) {
+        let mut paths = self.paths.write().unwrap();
+        for paths in paths.values_mut() {
+            paths.retain(|_, path| Path::new(path).exists());
+        }
+        paths.retain(|_, paths| !paths.is_empty());
+    }
+
+    /// Returns the path of each node.
+    pub fn origins(&self) -> BTreeMap<String, String> {
+        self.paths
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(name, paths)| Some((name.clone(), paths.values().next_back()?.clone())))
+            .collect()
+    }
+
+    /// Returns all the paths of the nodes defined in several roots, from the used one.
+    pub fn duplicates(&self) -> BTreeMap<String, Vec<String>> {
+        self.paths
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(name, paths)| (name.clone(), paths.values().rev().cloned().collect()))
+            .collect()
+    }
+
+    fn path(&self, name: &str) -> Option<String> {
+        self.paths
+            .read()
+            .unwrap()
+            .get(name)
+            .and_then(|paths| paths.values().next_back().cloned())
+    }
+
+    pub fn names(&self) -> BTreeSet<String> {
+        self.paths
+            .read()
+            .unwrap()
+            .keys()
+            .chain(self.sources.read().unwrap().keys())
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        if let Some(path) = self.path(name) {
+            return Ok(Some(fs::read_to_string(path)?));
+        }
+        Ok(self.sources.read().unwrap().get(name).cloned())
+    }
+
+    pub fn stamp(&self, name: &str) -> Result<Option<Stamp>> {
+        if let Some(path) = self.path(name) {
+            return match fs::metadata(path) {
+                Ok(metadata) => Ok(Some(Stamp::Modified {
+                    time: metadata.modified()?,
+                    len: metadata.len(),
+                })),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            };
+        }
+
+        Ok(self
+            .sources
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|source| Stamp::Hash(hash_source(source))))
+    }
+}
+
+struct NodeCacheEntry<T> {
+    output: T,
+    stamp: Option<Stamp>,
+}
+
+/// The layout of the cached nodes, which should be bumped whenever the `CompactedNode` is changed.
+pub const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// Hashes the source, including the version of the builder and the layout of the cached nodes.
+///
+/// Note that the hash should be stable between the runs, as it is stored in the persistent cache.
+/// The entries stored by the other versions are keyed differently, so they are never loaded.
+pub fn hash_source(source: &str) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update([0]);
+    hasher.update(CACHE_FORMAT_VERSION.to_le_bytes());
+    hasher.update(source);
+    let hash = hasher.finalize();
+
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// The built nodes of a single root, backed by the shared sources.
+///
+/// The stale nodes are rebuilt when their sources, or the sources of the nodes they use, are changed.
+pub struct NodeCache<T: Build> {
+    sources: Arc<NodeSources>,
+    caches: RefCell<HashMap<String, NodeCacheEntry<T::Output>>>,
+    // name -> the nodes used to build it
+    dependencies: RefCell<HashMap<String, BTreeSet<String>>>,
+    building: RefCell<Vec<String>>,
+}
+
+impl<T: Build> NodeCache<T> {
+    pub fn new(paths: HashMap<String, String>) -> Self {
+        Self::with_sources(Arc::new(NodeSources::with_paths(paths)))
+    }
+
+    pub fn with_sources(sources: Arc<NodeSources>) -> Self {
+        Self {
+            sources,
+            caches: RefCell::default(),
+            dependencies: RefCell::default(),
+            building: RefCell::default(),
+        }
+    }
+
+    pub fn sources(&self) -> &Arc<NodeSources> {
+        &self.sources
+    }
+
+    pub fn add_source(&self, name: String, source: String) {
+        self.sources.add_source(name, source);
+    }
+
+    pub fn add_path(&self, name: String, path: String) {
+        self.sources.add_path(name, path);
+    }
+
+    pub fn add_path_with_priority(&self, name: String, path: String, priority: usize) {
+        self.sources.add_path_with_priority(name, path, priority);
+    }
+
+    pub fn remove_missing_paths(&self) {
+        self.sources.remove_missing_paths();
+    }
+
+    pub fn names(&self) -> BTreeSet<String> {
+        self.sources.names()
+    }
+
+    pub fn get_source(&self, name: &str) -> Result<Option<String>> {
+        self.sources.get(name)
+    }
+
+    pub fn get(&self, name: &str, root: &NodeRoot) -> Result<T::Output> {
+        if let Some(parent) = self.building.borrow().last() {
+            self.dependencies
+                .borrow_mut()
+                .entry(parent.clone())
+                .or_default()
+                .insert(name.to_string());
+        }
+
+        self.evict_stale(name)?;
+        if let Some(cache) = self.caches.borrow().get(name) {
+            let mut variables = vec![];
+            return Ok(cache.output.clone_safe(&root.seed, &mut variables));
+        }
+
+        let stamp = self.sources.stamp(name)?;
+        if let Some(source) = self.sources.get(name)? {
+            if let Some(output) = self.load_and_store(name, root, &source, stamp.clone()) {
+                return Ok(output);
+            }
+            return self.build_and_store(name, root, source, stamp);
+        }
+
+        TensorNodeError::NoSuchNode {
+            name: name.to_string(),
+            candidates: self.names(),
+        }
+        .into()
+    }
+
+    fn build_and_store(
+        &self,
+        name: &str,
+        root: &NodeRoot,
+        source: String,
+        stamp: Option<Stamp>,
+    ) -> Result<T::Output> {
+        let hash = hash_source(&source);
+
+        // TODO: detect cycling
+        self.building.borrow_mut().push(name.to_string());
+        let result = T::build(root, name, source);
+        self.building.borrow_mut().pop();
+        let output = result?;
+
+        if let Some(binary) = T::dump(&output) {
+            let dependencies = self.get_dependency_hashes(name);
+
+            // note: the persistent cache is optional, so its errors are ignored
+            if let Some(disk_cache) = &root.disk_cache {
+                disk_cache
+                    .save(name, hash, dependencies.clone(), binary.clone())
+                    .ok();
+            }
+            root.memory_cache
+                .save(name, hash, dependencies, Arc::new(binary));
+        }
+
+        let mut variables = vec![];
+        let cloned = output.clone_safe(&root.seed, &mut variables);
+
+        let entry = NodeCacheEntry { output, stamp };
+        self.caches.borrow_mut().insert(name.to_string(), entry);
+        Ok(cloned)
+    }
+
+    /// Loads the node from the shared or persistent cache, if its dependencies are not changed.
+    fn load_and_store(
+        &self,
+        name: &str,
+        root: &NodeRoot,
+        source: &str,
+        stamp: Option<Stamp>,
+    ) -> Option<T::Output> {
+        let hash = hash_source(source);
+        let (dependencies, binary) = match root.memory_cache.load(name, hash) {
+            Some(entry) => entry,
+            None => {
+                let (dependencies, binary) = root.disk_cache.as_ref()?.load(name, hash)?;
+                let binary = Arc::new(binary);
+                root.memory_cache
+                    .save(name, hash, dependencies.clone(), binary.clone());
+                (dependencies, binary)
+            }
+        };
+
+        for (dependency, hash) in &dependencies {
+            let source = self.sources.get(dependency).ok()??;
+            if hash_source(&source) != *hash {
+                return None;
+            }
+        }
+        let output = T::load(&binary)?;
+
+        let mut variables = vec![];
+        let cloned = output.clone_safe(&root.seed, &mut variables);
+
+        let entry = NodeCacheEntry { output, stamp };
+        self.caches.borrow_mut().insert(name.to_string(), entry);
+        self.dependencies
+            .borrow_mut()
+            .insert(name.to_string(), dependencies.into_keys().collect());
+        Some(cloned)
+    }
+
+    /// Returns the hashes of the sources of the dependencies, transitively.
+    fn get_dependency_hashes(&self, name: &str) -> BTreeMap<String, u64> {
+        let dependencies = self.dependencies.borrow();
+
+        let mut hashes = BTreeMap::new();
+        let mut stack = vec![name];
+        while let Some(name) = stack.pop() {
+            for dependency in dependencies.get(name).into_iter().flatten() {
+                if hashes.contains_key(dependency) {
+                    continue;
+                }
+                if let Ok(Some(source)) = self.sources.get(dependency) {
+                    hashes.insert(dependency.clone(), hash_source(&source));
+                    stack.push(dependency);
+                }
+            }
+        }
+        hashes
+    }
+
+    /// Evicts the node if the source of it or its dependencies has been changed.
+    fn evict_stale(&self, name: &str) -> Result<()> {
+        let stamp = match self.caches.borrow().get(name) {
+            Some(entry) => entry.stamp.clone(),
+            None => return Ok(()),
+        };
+        if stamp != self.sources.stamp(name)? {
+            self.evict(name);
+            return Ok(());
+        }
+
+        // note: evicting a dependency evicts this node too
+        let dependencies = self.dependencies.borrow().get(name).cloned();
+        for dependency in dependencies.iter().flatten() {
+            self.evict_stale(dependency)?;
+        }
+        Ok(())
+    }
+
+    /// Evicts the node and its dependents transitively.
+    fn evict(&self, name: &str) {
+        if self.caches.borrow_mut().remove(name).is_none() {
+            return;
+        }
+        self.dependencies.borrow_mut().remove(name);
+
+        let dependents: Vec<_> = self
+            .dependencies
+            .borrow()
+            .iter()
+            .filter(|(_, dependencies)| dependencies.contains(name))
+            .map(|(dependent, _)| dependent.clone())
+            .collect();
+        for dependent in dependents {
+            self.evict(&dependent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tensor::TensorNode;
+
+    fn new_root(cache_dir: &Path) -> NodeRoot {
+        let mut root = NodeRoot::new(Some("../n3-torch/ffi/python/n3"));
+        root.set_cache_dir(cache_dir.to_path_buf());
+        root
+    }
+
+    #[test]
+    fn test_compact_node() {
+        let root = NodeRoot::new(Some("../n3-torch/ffi/python/n3"));
+        let node: TensorNode = root.get("LeNet5").unwrap().into();
+
+        let compacted = CompactedNode::compact(&node).unwrap();
+        let binary = bincode::serialize(&compacted).unwrap();
+        let compacted: CompactedNode = bincode::deserialize(&binary).unwrap();
+        assert_eq!(compacted.decompact(), node);
+    }
+
+    #[test]
+    fn test_memory_cache() {
+        let root = NodeRoot::new(Some("../n3-torch/ffi/python/n3"));
+        let node: TensorNode = root.get("LeNet5").unwrap().into();
+
+        // the spawned roots reuse the compacted node
+        let spawned = root.share().spawn();
+        let source = spawned.get_source("LeNet5").unwrap();
+        assert!(spawned
+            .memory_cache
+            .load("LeNet5", hash_source(&source))
+            .is_some());
+
+        let spawned_node: TensorNode = spawned.get("LeNet5").unwrap().into();
+        assert_eq!(spawned_node, node);
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let cache_dir = std::env::temp_dir().join("n3-test-disk-cache");
+        fs::remove_dir_all(&cache_dir).ok();
+
+        let block_relu = "
+node MyBlock:
+    1. Linear + Relu
+";
+        let block_dropout = "
+node MyBlock:
+    1. Linear + Dropout
+";
+        let model = "
+node MyNode:
+    0. Input    = 10
+    1. MyBlock  = 20
+";
+        let build = |block: &str| {
+            let root = new_root(&cache_dir);
+            root.add_source("MyBlock".to_string(), block.to_string());
+            root.add_source("MyNode".to_string(), model.to_string());
+            root.get("MyNode").unwrap().build(&root).unwrap()
+        };
+
+        // Step 1. build & store
+        let code = build(block_relu);
+        let hash = hash_source(model);
+        assert!(DiskCache::new(cache_dir.clone())
+            .load("MyNode", hash)
+            .is_some());
+
+        // the temporary files should be renamed into place
+        for entry in fs::read_dir(&cache_dir).unwrap() {
+            let path = entry.unwrap().path();
+            assert_eq!(path.extension().unwrap(), "bin");
+        }
+
+        // Step 2. load
+        assert_eq!(build(block_relu), code);
+
+        // Step 3. the changed dependency should not be loaded
+        assert_ne!(build(block_dropout), code);
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
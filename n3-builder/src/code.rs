@@ -1,9 +1,11 @@
 use std::rc::Rc;
 
+use crate::ast;
 use crate::error::Result;
 use crate::externs::PythonScripts;
 use crate::nodes::NodeRoot;
 use crate::tensor::IRData;
+use crate::variable::BuildValue;
 
 pub use n3_program::code::*;
 
@@ -15,6 +17,10 @@ pub trait DataFromIR {
     fn from_ir(data: IRData) -> Self;
 }
 
+pub trait ShapesFromIR {
+    fn from_ir(input: Option<&ast::Shapes>, output: Option<&ast::Shapes>) -> Self;
+}
+
 impl AddScripts for Code {
     fn add_scripts(&self, root: &NodeRoot, scripts: &mut PythonScripts) -> Result<()> {
         match self {
@@ -34,6 +40,37 @@ impl DataFromIR for CodeData {
                 .into_table(),
             input: data.input,
             output: data.output,
+            shared: data.shared,
+        }
+    }
+}
+
+impl ShapesFromIR for CodeShapes {
+    fn from_ir(input: Option<&ast::Shapes>, output: Option<&ast::Shapes>) -> Self {
+        Self {
+            input: build_shapes(input),
+            output: build_shapes(output),
         }
     }
 }
+
+fn build_shapes(shapes: Option<&ast::Shapes>) -> Shapes {
+    match shapes {
+        Some(shapes) => shapes
+            .0
+            .borrow()
+            .iter()
+            .map(|(name, shape)| {
+                let shape = shape.as_ref().map(|shape| {
+                    shape
+                        .0
+                        .iter()
+                        .map(|dim| dim.build().unwrap_uint())
+                        .collect()
+                });
+                (name.clone(), shape)
+            })
+            .collect(),
+        None => Shapes::new(),
+    }
+}
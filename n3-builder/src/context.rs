@@ -14,15 +14,18 @@ type NodeNameRef<'a> = &'a [String];
 
 pub struct Context<'a> {
     pub root: &'a NodeRoot,
+    // the source of the file being built
+    pub source: Rc<String>,
     parent: BTreeMap<NodeName, RefGraph>,
     children: BTreeMap<NodeName, BTreeMap<String, TensorNode>>,
     uses: BTreeMap<String, TensorNode>,
 }
 
 impl<'a> Context<'a> {
-    pub fn new(root: &'a NodeRoot) -> Self {
+    pub fn new(root: &'a NodeRoot, source: Rc<String>) -> Self {
         Context {
             root,
+            source,
             parent: Default::default(),
             children: Default::default(),
             uses: Default::default(),
@@ -42,6 +45,10 @@ impl<'a> Context<'a> {
             .insert(node_name, child);
     }
 
+    pub fn add_use(&mut self, name: String, node: TensorNode) {
+        self.uses.insert(name, node);
+    }
+
     pub fn get(&mut self, parent: NodeNameRef, name: &str) -> Result<TensorNode> {
         let mut parent = parent.to_vec();
         while !parent.is_empty() {
@@ -67,6 +74,21 @@ pub trait Build: CloneSafe {
     fn build(root: &NodeRoot, name: &str, source: String) -> Result<Self::Output>
     where
         Self: Sized;
+
+    /// Compacts the output to be stored in the persistent cache, if possible.
+    fn dump(_output: &Self::Output) -> Option<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    fn load(_binary: &[u8]) -> Option<Self::Output>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 pub trait CloneSafe {
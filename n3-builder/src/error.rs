@@ -1,12 +1,18 @@
 use std::collections::BTreeSet;
+use std::fmt;
 use std::path::PathBuf;
 
 use glob::{GlobError, PatternError};
+use semver::Error as SemverError;
+use serde_yaml::Error as YamlError;
+use toml::de::Error as TomlDeError;
+use toml::ser::Error as TomlSerError;
 
 use n3_parser::error::ParseError;
 use n3_program::error::Error as CompactError;
 
 use crate::ast;
+use crate::suggest::fmt_suggestions;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -17,6 +23,7 @@ pub enum Error {
     ExecBuildError(ExecBuildError),
     ExecError(ExecError),
     CompactError(CompactError),
+    PackageError(PackageError),
     ExternalError(ExternalError),
 }
 
@@ -44,12 +51,52 @@ pub enum ExecBuildError {
 pub enum ExecError {
     NoSuchDirectory { path: PathBuf },
     NotDirectory { path: PathBuf },
+    UnsupportedConfigFormat { path: PathBuf },
+    InvalidSweepSpace { spec: String },
+    InvalidOverride { spec: String },
+    NoSuchCheckpoint { from: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PackageError {
+    InvalidId {
+        id: String,
+    },
+    NoSuchPackage {
+        id: String,
+    },
+    NoMatchingVersion {
+        id: String,
+        req: semver::VersionReq,
+    },
+    ConflictedVersions {
+        id: String,
+        version: semver::Version,
+        req: semver::VersionReq,
+    },
+    NotInstalled {
+        id: String,
+        version: semver::Version,
+    },
+    NoSuchUse {
+        name: String,
+        author: String,
+    },
+    AmbiguousUse {
+        name: String,
+        author: String,
+        packages: Vec<String>,
+    },
+    UnsupportedSource {
+        source: String,
+    },
 }
 
 #[derive(Debug, PartialEq)]
 pub enum TensorNodeError {
     NoSuchNode {
         name: String,
+        candidates: BTreeSet<String>,
     },
     MismatchedName {
         expected: String,
@@ -90,12 +137,21 @@ pub enum GraphError {
         expected: ast::LetType,
         given: Option<ast::LetType>,
     },
+    ViolatedConstraint {
+        name: String,
+        value: ast::Value,
+        constraint: ast::LetConstraint,
+    },
 }
 
 #[derive(Debug, PartialEq)]
 pub enum GraphNodeError {
     EmptyCalls,
     UnexpectedShapes,
+    UnexpectedGuard,
+    UnresolvedGuard {
+        value: ast::Value,
+    },
     NoSuchInput {
         out: ast::Out,
     },
@@ -119,6 +175,7 @@ pub enum GraphCallError {
     UnexpectedInputs,
     UnexpectedArgs,
     UnexpectedRepeat,
+    UnexpectedShared,
     GenericListInputShape {
         index: usize,
     },
@@ -163,6 +220,16 @@ pub enum GraphCallError {
         expected: Vec<String>,
         given: Vec<String>,
     },
+    MismatchedSharedNode {
+        shared: String,
+        expected: String,
+        given: String,
+    },
+    MismatchedNodeType {
+        name: String,
+        expected: ast::LetNodeType,
+        given: ast::LetNodeType,
+    },
 }
 
 #[derive(Debug)]
@@ -182,6 +249,10 @@ pub enum ExternalError {
     IOError(std::io::Error),
     GlobError(GlobError),
     PatternError(PatternError),
+    SemverError(SemverError),
+    TomlDeError(TomlDeError),
+    TomlSerError(TomlSerError),
+    YamlError(YamlError),
 }
 
 impl PartialEq for Error {
@@ -189,6 +260,7 @@ impl PartialEq for Error {
         match (self, other) {
             (Self::ParseError(a), Self::ParseError(b)) => a.eq(b),
             (Self::BuildError(a), Self::BuildError(b)) => a.eq(b),
+            (Self::PackageError(a), Self::PackageError(b)) => a.eq(b),
             (Self::ExternalError(a), Self::ExternalError(b)) => a.eq(b),
             _ => false,
         }
@@ -230,7 +302,11 @@ impl PartialEq for ExternalError {
             (self, other),
             (Self::IOError(_), Self::IOError(_))
                 | (Self::GlobError(_), Self::GlobError(_))
-                | (Self::PatternError(_), Self::PatternError(_)),
+                | (Self::PatternError(_), Self::PatternError(_))
+                | (Self::SemverError(_), Self::SemverError(_))
+                | (Self::TomlDeError(_), Self::TomlDeError(_))
+                | (Self::TomlSerError(_), Self::TomlSerError(_))
+                | (Self::YamlError(_), Self::YamlError(_)),
         )
     }
 }
@@ -265,6 +341,12 @@ impl From<CompactError> for Error {
     }
 }
 
+impl From<PackageError> for Error {
+    fn from(error: PackageError) -> Self {
+        Self::PackageError(error)
+    }
+}
+
 impl<T> From<ExecBuildError> for Result<T> {
     fn from(error: ExecBuildError) -> Self {
         Err(Error::from(error))
@@ -277,6 +359,12 @@ impl<T> From<ExecError> for Result<T> {
     }
 }
 
+impl<T> From<PackageError> for Result<T> {
+    fn from(error: PackageError) -> Self {
+        Err(Error::from(error))
+    }
+}
+
 macro_rules! impl_into_build_error(
     ($t:ident) => {
         impl From<$t> for BuildError {
@@ -323,6 +411,10 @@ impl_into_build_error!(LinkError);
 
 impl_into_external_error!(GlobError);
 impl_into_external_error!(PatternError);
+impl_into_external_error!(SemverError);
+impl_into_external_error!(TomlDeError);
+impl_into_external_error!(TomlSerError);
+impl_into_external_error!(YamlError);
 
 impl<T> From<Box<T>> for Error
 where
@@ -338,3 +430,351 @@ impl From<std::io::Error> for Error {
         Self::ExternalError(ExternalError::IOError(error))
     }
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError(e) => write!(f, "parse error: {}", e),
+            Self::BuildError(e) => write!(f, "build error: {}", e),
+            Self::ExecBuildError(e) => write!(f, "exec build error: {}", e),
+            Self::ExecError(e) => write!(f, "exec error: {}", e),
+            Self::CompactError(e) => write!(f, "compact error: {}", e),
+            Self::PackageError(e) => write!(f, "package error: {}", e),
+            Self::ExternalError(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TensorNodeError(e) => e.fmt(f),
+            Self::GraphError(e) => e.fmt(f),
+            Self::GraphNodeError(e) => e.fmt(f),
+            Self::GraphCallError(e) => e.fmt(f),
+            Self::LinkError(e) => e.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for ExecBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedWiths => write!(f, "exec nodes cannot have `with` blocks"),
+            Self::UnexpectedChildren => write!(f, "exec nodes cannot have child nodes"),
+            Self::EmptyGraph => write!(f, "exec nodes should have at least one graph node"),
+            Self::MismatchedNodeType { expected, given } => write!(
+                f,
+                "expected a `{}`, but given a `{}`",
+                fmt_node_type(*expected),
+                fmt_node_type(*given),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchDirectory { path } => {
+                write!(f, "no such directory: \"{}\"", path.display())
+            }
+            Self::NotDirectory { path } => write!(f, "not a directory: \"{}\"", path.display()),
+            Self::UnsupportedConfigFormat { path } => write!(
+                f,
+                "unsupported config file: \"{}\", expected `.toml` or `.yaml`",
+                path.display()
+            ),
+            Self::InvalidSweepSpace { spec } => write!(f, "invalid sweep space: `{}`", spec),
+            Self::InvalidOverride { spec } => {
+                write!(
+                    f,
+                    "invalid override `{}`, expected `node.variable=value`",
+                    spec
+                )
+            }
+            Self::NoSuchCheckpoint { from } => write!(
+                f,
+                "no such checkpoint: `{}`, expected a work id or a path",
+                from
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidId { id } => {
+                write!(
+                    f,
+                    "invalid package `{}`, expected `author/name` of letters, digits, `_` or `-`",
+                    id
+                )
+            }
+            Self::NoSuchPackage { id } => write!(f, "no such package `{}`", id),
+            Self::NoMatchingVersion { id, req } => {
+                write!(f, "no version of package `{}` matches `{}`", id, req)
+            }
+            Self::ConflictedVersions { id, version, req } => write!(
+                f,
+                "package `{}` is resolved as {}, but `{}` is required",
+                id, version, req
+            ),
+            Self::NotInstalled { id, version } => write!(
+                f,
+                "package `{}` {} is not installed; run `n3 pkg install`",
+                id, version
+            ),
+            Self::NoSuchUse { name, author } => {
+                write!(f, "no package by `{}` has node `{}`", author, name)
+            }
+            Self::AmbiguousUse {
+                name,
+                author,
+                packages,
+            } => write!(
+                f,
+                "node `{}` is defined in several packages by `{}`: {}",
+                name,
+                author,
+                packages.join(", ")
+            ),
+            Self::UnsupportedSource { source } => {
+                write!(f, "cannot use the nodes from \"{}\" yet", source)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TensorNodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchNode { name, candidates } => {
+                write!(f, "no such node `{}`", name)?;
+                fmt_suggestions(f, name, candidates)
+            }
+            Self::MismatchedName { expected, given } => write!(
+                f,
+                "the node should be named `{}`, but given `{}`",
+                expected, given
+            ),
+            Self::MismatchedType { expected, given } => write!(
+                f,
+                "expected a {:?} node, but given a {:?} node",
+                expected, given
+            ),
+        }
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchVariable { name, candidates } => {
+                write!(f, "no such variable `{}`", name)?;
+                fmt_suggestions(f, name, candidates)
+            }
+            Self::UnexpectedNodeVariable { name } => {
+                write!(f, "the variable `{}` cannot be a node", name)
+            }
+            Self::UnparsableString { name, value, ty } => {
+                write!(f, "cannot parse {:?} for the variable `{}`", value, name)?;
+                match ty {
+                    Some(ty) => write!(f, " as `{:?}`", ty),
+                    None => Ok(()),
+                }
+            }
+            Self::DuplicatedVariable { name } => write!(f, "duplicated variable `{}`", name),
+            Self::CycledVariables { names } => {
+                let names: Vec<_> = names.iter().map(|x| format!("`{}`", x)).collect();
+                write!(f, "the variables are cycled: {}", names.join(", "))
+            }
+            Self::EmptyValue { name, expected } => write!(
+                f,
+                "the variable `{}` should be given a `{:?}` value",
+                name, expected
+            ),
+            Self::MismatchedType {
+                name,
+                expected,
+                given,
+            } => {
+                write!(f, "the variable `{}` expected `{:?}`", name, expected)?;
+                fmt_given_type(f, given)
+            }
+            Self::ViolatedConstraint {
+                name,
+                value,
+                constraint,
+            } => write!(
+                f,
+                "the variable `{}` should be in `{:?}`, but given `{:?}`",
+                name, constraint, value
+            ),
+        }
+    }
+}
+
+impl fmt::Display for GraphNodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyCalls => write!(f, "the graph node should call at least one node"),
+            Self::UnexpectedShapes => write!(f, "the graph node cannot have shapes"),
+            Self::UnexpectedGuard => write!(f, "the graph node cannot have a guard"),
+            Self::UnresolvedGuard { value } => {
+                write!(f, "cannot resolve the guard `{:?}` into a bool", value)
+            }
+            Self::NoSuchInput { out } => write!(f, "no such input `{:?}`", out),
+            Self::MismatchedId { expected, given } => write!(
+                f,
+                "the graph id should be {}, but given {}",
+                expected, given
+            ),
+            Self::MismatchedSize { expected, given } => write!(
+                f,
+                "expected {} call(s) ({}), but given {}",
+                expected.len(),
+                expected.join(", "),
+                given
+            ),
+            Self::MismatchedShapesExistence { expected, given } => match (expected, given) {
+                (true, false) => write!(f, "the graph node should have shapes"),
+                _ => write!(f, "the graph node cannot have shapes"),
+            },
+        }
+    }
+}
+
+impl fmt::Display for GraphCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyInputs => write!(f, "the call should be given inputs"),
+            Self::UnexpectedInputs => write!(f, "the call cannot be given inputs"),
+            Self::UnexpectedArgs => write!(f, "the call cannot be given arguments"),
+            Self::UnexpectedRepeat => write!(f, "the call cannot be repeated"),
+            Self::UnexpectedShared => write!(f, "the call cannot be shared"),
+            Self::GenericListInputShape { index } => {
+                write!(f, "the shape of the input #{} cannot be generic", index)
+            }
+            Self::GenericShape { name } => write!(f, "the shape `{}` cannot be generic", name),
+            Self::GenericShapes => write!(f, "the shapes cannot be generic"),
+            Self::MismatchedName { expected, given } => write!(
+                f,
+                "expected one of `{}`, but given `{}`",
+                expected.join("`, `"),
+                given
+            ),
+            Self::MismatchedSize { expected, given } => write!(
+                f,
+                "expected {} item(s) ({}), but given {}",
+                expected.len(),
+                expected.join(", "),
+                given
+            ),
+            Self::MismatchedInputsType { expected, given } => write!(
+                f,
+                "expected the {:?} inputs, but given the {:?} inputs",
+                expected, given
+            ),
+            Self::MismatchedRepeat { expected, given } => match (expected, given) {
+                (true, false) => write!(f, "the call should be repeated"),
+                _ => write!(f, "the call cannot be repeated"),
+            },
+            Self::MismatchedAxis {
+                val_min,
+                val_max,
+                given,
+            } => write!(
+                f,
+                "the axis should be in [{}, {}], but given {}",
+                val_min, val_max, given
+            ),
+            Self::MismatchedArgType { expected, given } => {
+                write!(f, "the argument expected `{:?}`", expected)?;
+                fmt_given_type(f, given)
+            }
+            Self::MismatchedArgs { expected, given } => write!(
+                f,
+                "expected the arguments ({}), but given ({})",
+                expected.join(", "),
+                given.join(", ")
+            ),
+            Self::MismatchedShapes { expected, given } => write!(
+                f,
+                "expected {} shape(s), but given {}",
+                expected, given
+            ),
+            Self::MismatchedShapeKeys { expected, given } => write!(
+                f,
+                "expected the shapes ({}), but given ({})",
+                expected.join(", "),
+                given.join(", ")
+            ),
+            Self::MismatchedSharedNode {
+                shared,
+                expected,
+                given,
+            } => write!(
+                f,
+                "the shared tag `{}` belongs to `{}`, but given `{}`",
+                shared, expected, given
+            ),
+            Self::MismatchedNodeType {
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "the node `{}` expected a `{}`, but given a `{}`",
+                name,
+                fmt_node_type(*expected),
+                fmt_node_type(*given),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MismatchedDim { expected, given } => write!(
+                f,
+                "expected the dim `{:?}`, but given `{:?}`",
+                expected, given
+            ),
+            Self::MismatchedShape { expected, given } => write!(
+                f,
+                "expected the shape `{:?}`, but given `{:?}`",
+                expected, given
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "io error: {}", e),
+            Self::GlobError(e) => write!(f, "glob error: {}", e),
+            Self::PatternError(e) => write!(f, "pattern error: {}", e),
+            Self::SemverError(e) => write!(f, "version error: {}", e),
+            Self::TomlDeError(e) => write!(f, "toml error: {}", e),
+            Self::TomlSerError(e) => write!(f, "toml error: {}", e),
+            Self::YamlError(e) => write!(f, "yaml error: {}", e),
+        }
+    }
+}
+
+fn fmt_given_type(f: &mut fmt::Formatter<'_>, given: &Option<ast::LetType>) -> fmt::Result {
+    match given {
+        Some(given) => write!(f, ", but given `{:?}`", given),
+        None => write!(f, ", but given nothing"),
+    }
+}
+
+fn fmt_node_type(ty: ast::LetNodeType) -> String {
+    format!("{:?}", ast::LetType::Node(Some(ty)))
+}
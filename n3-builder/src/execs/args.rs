@@ -1,6 +1,7 @@
 use std::ops::Deref;
 
 use super::ir::ExecIR;
+use super::overrides::Override;
 use super::program::Program;
 use super::root::ExecRoot;
 use super::var::Vars;
@@ -10,6 +11,7 @@ pub struct Args<'a> {
     pub(super) root: &'a mut ExecRoot,
     pub(super) ir: ExecIR,
     pub(super) args: Vars,
+    pub(super) overrides: Vec<Override>,
 }
 
 impl<'a> Deref for Args<'a> {
@@ -21,14 +23,36 @@ impl<'a> Deref for Args<'a> {
 }
 
 impl<'a> Args<'a> {
+    /// Overrides the variable of the nested node, e.g. `model.kernel size`.
+    ///
+    /// The path is validated when building the program.
+    pub fn set_nested(&mut self, path: &str, value: &str) -> Result<()> {
+        self.add_override(Override::new(path, value)?);
+        Ok(())
+    }
+
+    pub fn add_override(&mut self, value: Override) {
+        self.overrides.push(value);
+    }
+
+    pub fn overrides(&self) -> &[Override] {
+        &self.overrides
+    }
+
     pub fn build_uncompacted(self) -> Result<Program> {
-        self.ir.build(&self.root)
+        self.ir.build(&self.root, &self.overrides)
     }
 
-    pub fn build_with_env(self) -> Result<Vec<u8>> {
-        let mut program = self.ir.build(&self.root)?;
+    pub fn build_uncompacted_with_env(self) -> Result<Program> {
+        let mut program = self.ir.build(&self.root, &self.overrides)?;
         self.root.attach_env(&mut program);
-        program.save_to_binary().map_err(|e| e.into())
+        Ok(program)
+    }
+
+    pub fn build_with_env(self) -> Result<Vec<u8>> {
+        self.build_uncompacted_with_env()?
+            .save_to_binary()
+            .map_err(|e| e.into())
     }
 
     pub fn build(self) -> Result<Vec<u8>> {
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::rc::Rc;
 
+use super::overrides::Override;
 use super::program::{Program, PROGRAM_MAIN};
 use super::var::Vars;
 use crate::ast;
@@ -29,7 +30,7 @@ impl ExecIR {
         Vars::from(self.data.graph.borrow().variables().clone())
     }
 
-    pub fn build(self, root: &NodeRoot) -> Result<Program> {
+    pub fn build(self, root: &NodeRoot, overrides: &[Override]) -> Result<Program> {
         // prune graph
         let mut nodes = BTreeMap::new();
 
@@ -63,19 +64,14 @@ impl ExecIR {
                         Err(e) => return Some(Err(e)),
                     };
 
-                    if node.ty != ty {
-                        // the normal extern node can be applied into normal node.
-                        if !(node.ty == ast::LetNodeType::Extern(ast::ExternNodeType::Default)
-                            && ty == ast::LetNodeType::Default)
-                        {
-                            return Some(
-                                ExecBuildError::MismatchedNodeType {
-                                    expected: ty,
-                                    given: node.ty,
-                                }
-                                .into(),
-                            );
-                        }
+                    if !node.is_compatible_with(ty) {
+                        return Some(
+                            ExecBuildError::MismatchedNodeType {
+                                expected: ty,
+                                given: node.ty,
+                            }
+                            .into(),
+                        );
                     }
 
                     nodes.insert(var_name, node);
@@ -87,6 +83,11 @@ impl ExecIR {
             })
             .collect::<Result<_>>()?;
 
+        // override the nested variables
+        for value in overrides {
+            value.apply(&nodes)?;
+        }
+
         // link nodes
         for links in &self.links {
             // the calls should not be empty.
@@ -101,6 +102,12 @@ impl ExecIR {
             }
         }
 
+        // resolve the shapes of the nodes
+        let shapes = nodes
+            .iter()
+            .map(|(k, v)| (k.clone(), v.get_shapes()))
+            .collect();
+
         // build nodes
         let nodes: BTreeMap<_, _> = nodes
             .into_iter()
@@ -122,6 +129,7 @@ impl ExecIR {
             graph,
             nodes,
             scripts,
+            shapes,
         })
     }
 }
@@ -1,20 +1,32 @@
 mod args;
 mod ir;
+mod overrides;
 mod program;
 mod root;
+mod sweep;
 mod var;
 
 pub use n3_program::execs::dirs;
 
 pub use self::args::Args;
 pub use self::ir::ExecIR;
+pub use self::overrides::Override;
 pub use self::program::Program;
-pub use self::root::{ExecRoot, ExecRootConfig};
-pub use self::var::{GlobalVars, Vars, QUERY_SPLIT_1};
+pub use self::root::{ExecRoot, ExecRootConfig, SharedExecRoot};
+pub use self::sweep::{Space, Sweep, SweepSummary, Trial, TrialStatus};
+pub use self::var::{Config, ConfigValue, GlobalVars, Vars, QUERY_SPLIT_1};
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast;
+
+    /// Creates a root, which stores the built nodes into a temporary directory.
+    fn new_root(envs: GlobalVars) -> ExecRoot {
+        let mut root = ExecRoot::try_new(envs, Default::default()).unwrap();
+        root.set_cache_dir(std::env::temp_dir().join("n3-test-cache"));
+        root
+    }
 
     #[test]
     fn test_build_ic() {
@@ -22,7 +34,7 @@ mod tests {
         envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
         envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
             .unwrap();
-        let mut root = ExecRoot::try_new(envs, Default::default()).unwrap();
+        let mut root = new_root(envs);
 
         let args = root.get("DummyImageClassification").unwrap();
         args.set("data", "Mnist").unwrap();
@@ -48,5 +60,875 @@ mod tests {
 
             assert_eq!(program, program_decompacted);
         }
+
+        // dumping & loading the text formats
+        {
+            let json = program.to_json().unwrap();
+            let program_json = Program::from_json(&json).unwrap();
+            assert_eq!(program, program_json);
+            assert_eq!(program_json.to_json().unwrap(), json);
+
+            let yaml = program.to_yaml().unwrap();
+            let program_yaml = Program::from_yaml(&yaml).unwrap();
+            assert_eq!(program, program_yaml);
+            assert_eq!(program_yaml.to_yaml().unwrap(), yaml);
+        }
+    }
+
+    #[test]
+    fn test_build_deterministic() {
+        let build = |warm_up: bool| {
+            let envs = GlobalVars::default();
+            envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+            envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+                .unwrap();
+            let mut root = new_root(envs);
+
+            // the other nodes built ahead should not change the ids
+            if warm_up {
+                root.get("DummyImageClassification").unwrap();
+                root.check("Conv2D").unwrap();
+                root.check("LeNet5").unwrap();
+            }
+
+            let args = root.get("DummyImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "LeNet5").unwrap();
+            args.set("epoch", "1").unwrap();
+            args.set("batch size", "10").unwrap();
+            args.build_uncompacted().unwrap()
+        };
+
+        let program = build(false);
+        let program_warmed_up = build(true);
+        assert_eq!(
+            program.save_to_binary().unwrap(),
+            program_warmed_up.save_to_binary().unwrap(),
+        );
+        assert_eq!(
+            program.digest().unwrap(),
+            program_warmed_up.digest().unwrap()
+        );
+
+        // the digest should be kept after loading
+        let binary = program.save_to_binary().unwrap();
+        let program_loaded = Program::load(&*binary).unwrap();
+        assert_eq!(program.digest().unwrap(), program_loaded.digest().unwrap());
+        assert_eq!(program_loaded.save_to_binary().unwrap(), binary);
+    }
+
+    #[test]
+    fn test_arrange_id() {
+        use crate::code::{Code, CodeData};
+        use crate::externs::ExternCode;
+        use crate::graph::Table;
+
+        // the graphs are numbered by the order of building the nodes
+        let program = |ids: &[u64]| {
+            let nodes = ids
+                .iter()
+                .enumerate()
+                .map(|(index, &id)| {
+                    let name = format!("node{}", index);
+                    let code = ExternCode {
+                        ty: ast::ExternNodeType::Default,
+                        data: CodeData {
+                            name: name.clone(),
+                            graph: Table {
+                                id,
+                                variables: Default::default(),
+                            },
+                            input: Default::default(),
+                            output: Default::default(),
+                            shared: None,
+                        },
+                    };
+                    (name, Code::Extern(code))
+                })
+                .collect();
+
+            Program {
+                env: None,
+                graph: Default::default(),
+                nodes,
+                scripts: Default::default(),
+                shapes: Default::default(),
+            }
+        };
+
+        let program_a = program(&[1, 2, 3]);
+        let program_b = program(&[3, 1, 2]);
+        assert_eq!(
+            program_a.save_to_binary().unwrap(),
+            program_b.save_to_binary().unwrap(),
+        );
+        assert_eq!(program_a.digest().unwrap(), program_b.digest().unwrap());
+    }
+
+    #[test]
+    fn test_program_header() {
+        use n3_program::compact::{Header, FORMAT_VERSION, MAGIC};
+        use n3_program::error::Error;
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let args = root.get("DummyImageClassification").unwrap();
+        args.set("data", "Mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+        let binary = args.build().unwrap();
+
+        assert!(binary.starts_with(MAGIC));
+        let (header, payload) = Header::read(&binary).unwrap();
+        assert_eq!(header.format_version, FORMAT_VERSION);
+        assert_eq!(header.builder_version, env!("CARGO_PKG_VERSION"));
+        let program = Program::load(&*binary).unwrap();
+
+        // the legacy binaries, written by the builders before the header, have no magic bytes
+        let legacy = std::fs::read("tests/data/legacy/dummy_image_classification.bin").unwrap();
+        assert!(!legacy.starts_with(MAGIC));
+        let program_legacy = Program::load(&*legacy).unwrap();
+        assert_eq!(program_legacy.graph, program.graph);
+        assert_eq!(program_legacy.nodes, program.nodes);
+        assert!(program_legacy.scripts.keys().eq(program.scripts.keys()));
+        assert!(program_legacy.shapes.is_empty());
+
+        // the corrupted binaries
+        let mut binary_corrupted = binary.clone();
+        *binary_corrupted.last_mut().unwrap() ^= 0xff;
+        assert!(matches!(
+            Program::load(&*binary_corrupted),
+            Err(Error::MismatchedChecksum { .. }),
+        ));
+
+        // the binaries from the newer versions
+        let header_newer = Header {
+            format_version: FORMAT_VERSION + 1,
+            ..header.clone()
+        };
+        let binary_newer = header_newer.write(payload).unwrap();
+        assert_eq!(
+            Program::load(&*binary_newer).err(),
+            Some(Error::UnsupportedFormatVersion {
+                given: FORMAT_VERSION + 1,
+                supported: FORMAT_VERSION,
+            }),
+        );
+
+        let header_flagged = Header {
+            flags: header.flags | 1 << 31,
+            ..header
+        };
+        let binary_flagged = header_flagged.write(payload).unwrap();
+        assert_eq!(
+            Program::load(&*binary_flagged).err(),
+            Some(Error::UnsupportedFlags { flags: 1 << 31 }),
+        );
+
+        assert_eq!(Program::load(&b"hello"[..]).err(), Some(Error::NotProgram),);
+    }
+
+    #[test]
+    fn test_program_shapes() {
+        use n3_program::compact::{Header, FORMAT_VERSION};
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let args = root.get("DummyImageClassification").unwrap();
+        args.set("data", "Mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+        let mut program = args.build_uncompacted().unwrap();
+
+        // the shapes are resolved without the batch dimension
+        let shapes = &program.shapes["model"];
+        assert_eq!(shapes.input["x"], Some(vec![Some(1), Some(28), Some(28)]));
+        assert_eq!(shapes.output["x"], Some(vec![Some(10)]));
+
+        // the binaries from the previous version have no shapes
+        program.shapes.clear();
+        let binary = program.save_to_binary_with_flags(0).unwrap();
+        let payload = Header::read(&binary).unwrap().1;
+        // note: the empty map of shapes is encoded as its length only
+        let payload_previous = &payload[..payload.len() - 8];
+        let header_previous = Header {
+            format_version: FORMAT_VERSION - 1,
+            ..Header::new(payload_previous, 0)
+        };
+        let binary_previous = header_previous.write(payload_previous).unwrap();
+        assert_eq!(Program::load(&*binary_previous).unwrap(), program);
+    }
+
+    #[test]
+    fn test_program_compression() {
+        use n3_program::compact::{Header, FLAGS_DEFAULT, FLAG_DEDUP_SCRIPTS, FLAG_DEFLATE};
+        use n3_program::externs::PythonScript;
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let args = root.get("DummyImageClassification").unwrap();
+        args.set("data", "Mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+        let mut program = args.build_uncompacted().unwrap();
+
+        // the same scripts may be used by the other names
+        let aliases: Vec<_> = program
+            .scripts
+            .values()
+            .map(|script| PythonScript {
+                name: format!("{}Alias", &script.name),
+                source: script.source.clone(),
+            })
+            .collect();
+        assert!(!aliases.is_empty());
+        for script in aliases {
+            program.scripts.insert(script.name.clone(), script);
+        }
+
+        let binary = program.save_to_binary().unwrap();
+        assert_eq!(Header::read(&binary).unwrap().0.flags, FLAGS_DEFAULT);
+
+        let binaries: Vec<_> = [0, FLAG_DEDUP_SCRIPTS, FLAG_DEFLATE, FLAGS_DEFAULT]
+            .iter()
+            .map(|&flags| program.save_to_binary_with_flags(flags).unwrap())
+            .collect();
+        assert!(binaries[1].len() < binaries[0].len());
+        assert!(binaries[2].len() < binaries[0].len());
+        assert!(binaries[3].len() < binaries[1].len());
+
+        // the flags are transparent to loading
+        for binary in &binaries {
+            let program_loaded = Program::load(&**binary).unwrap();
+            assert_eq!(program_loaded, program);
+            assert_eq!(program_loaded.scripts.len(), program.scripts.len());
+            for (name, script) in &program.scripts {
+                assert_eq!(program_loaded.scripts[name].source, script.source);
+            }
+            assert_eq!(program_loaded.digest().unwrap(), program.digest().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_program_diff() {
+        use n3_program::{Change, ChangeKind};
+
+        let build = |epoch: &str| {
+            let envs = GlobalVars::default();
+            envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+            envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+                .unwrap();
+            let mut root = new_root(envs);
+
+            let args = root.get("DummyImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "LeNet5").unwrap();
+            args.set("epoch", epoch).unwrap();
+            args.set("batch size", "10").unwrap();
+            args.build_uncompacted().unwrap()
+        };
+
+        let program_a = build("1");
+        let mut program_b = build("2");
+        assert!(program_a.diff(&program_a).is_empty());
+
+        // swap the first two nodes in the model
+        match program_b.nodes.get_mut("model").unwrap() {
+            crate::Code::Node(model) => model.tensor_graph.swap(0, 1),
+            _ => unreachable!(),
+        }
+        // change the script
+        program_b.scripts.get_mut("Relu").unwrap().source += "\n";
+        // change the input shape of the model
+        program_b
+            .shapes
+            .get_mut("model")
+            .unwrap()
+            .input
+            .insert("x".to_string(), Some(vec![Some(1), Some(32), Some(32)]));
+
+        let changes = program_a.diff(&program_b);
+        assert_eq!(
+            changes[0],
+            Change {
+                path: "graph.epoch".to_string(),
+                kind: ChangeKind::Changed {
+                    from: "1".to_string(),
+                    to: "2".to_string(),
+                },
+            },
+        );
+        assert!(changes.contains(&Change {
+            path: "nodes.model.tensor_graph.MyConv#1".to_string(),
+            kind: ChangeKind::Moved { from: 1, to: 0 },
+        }));
+        assert!(changes.contains(&Change {
+            path: "nodes.model.tensor_graph.AssertShape#1".to_string(),
+            kind: ChangeKind::Moved { from: 0, to: 1 },
+        }));
+        assert!(changes
+            .iter()
+            .any(|change| change.path == "scripts.Relu.source"));
+        assert!(changes.contains(&Change {
+            path: "shapes.model.input.x".to_string(),
+            kind: ChangeKind::Changed {
+                from: "[1, 28, 28]".to_string(),
+                to: "[1, 32, 32]".to_string(),
+            },
+        }));
+        assert_eq!(changes.len(), 5);
+
+        // the removed nodes
+        match program_b.nodes.get_mut("model").unwrap() {
+            crate::Code::Node(model) => model.tensor_graph.pop(),
+            _ => unreachable!(),
+        };
+        let changes = program_a.diff(&program_b);
+        assert!(changes.contains(&Change {
+            path: "nodes.model.tensor_graph.Linear#2".to_string(),
+            kind: ChangeKind::Removed("Linear (extern)".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_config() {
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs.clone());
+        let mut args = root.get("DummyImageClassification").unwrap();
+
+        let config = Config::from_toml(
+            r#"
+data = "mnist"
+model = "LeNet5"
+epoch = 2
+batch_size = 10
+machines = ["cuda:0", "cuda:1"]
+
+[set]
+"optimizer.lr" = 0.5
+"#,
+        )
+        .unwrap();
+        config.apply_env(&envs).unwrap();
+        config.apply_args(&envs, &mut args).unwrap();
+
+        assert_eq!(
+            args.get_node_name("data", ast::LetNodeType::Extern(ast::ExternNodeType::Data))
+                .unwrap(),
+            "Mnist"
+        );
+        assert_eq!(
+            args.get("batch size").unwrap().borrow().value,
+            Some(10u64.into())
+        );
+        assert_eq!(
+            envs.get_string_list(dirs::N3_MACHINES).unwrap(),
+            ["cuda:0", "cuda:1"]
+        );
+        assert_eq!(
+            args.overrides(),
+            [Override::new("optimizer.lr", "0.5").unwrap()]
+        );
+
+        // the CLI args override the config
+        args.set("epoch", "3").unwrap();
+        args.add_override("optimizer.lr=0.1".parse().unwrap());
+
+        // dumping the effective config
+        let config_dumped = Config::from_vars(&[&args]).with_overrides(args.overrides());
+        assert_eq!(config_dumped.values["epoch"], ConfigValue::Int(3));
+        assert_eq!(
+            config_dumped.values["model"],
+            ConfigValue::String("LeNet5".to_string())
+        );
+        assert_eq!(
+            config_dumped.set["optimizer.lr"],
+            ConfigValue::String("0.1".to_string())
+        );
+        let toml = config_dumped.to_toml().unwrap();
+        assert_eq!(Config::from_toml(&toml).unwrap(), config_dumped);
+        let yaml = config_dumped.to_yaml().unwrap();
+        assert_eq!(Config::from_yaml(&yaml).unwrap(), config_dumped);
+
+        // the unknown keys
+        let config = Config::from_yaml("learning_rate: 0.1").unwrap();
+        assert!(config.apply_args(&envs, &mut args).is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let sweep = Sweep {
+            spaces: vec![
+                Space::parse_grid("batch size=32, 64").unwrap(),
+                Space::parse_grid("epoch=1,2,3").unwrap(),
+                Space::parse_random("learning rate=loguniform(1e-4,1e-2)").unwrap(),
+                Space::parse_random("seed=randint(0,9)").unwrap(),
+            ],
+            trials: 2,
+            seed: 42,
+        };
+        assert_eq!(
+            sweep.spaces[0],
+            (
+                "batch size".to_string(),
+                Space::Grid(vec!["32".to_string(), "64".to_string()])
+            ),
+        );
+
+        let variants = sweep.variants();
+        assert_eq!(variants.len(), 2 * 3 * 2);
+        assert_eq!(variants[0]["batch size"], "32");
+        assert_eq!(variants[0]["epoch"], "1");
+        assert_eq!(variants[1]["epoch"], "1");
+        assert_eq!(variants[2]["epoch"], "2");
+        assert_eq!(variants[11]["batch size"], "64");
+        for variant in &variants {
+            let lr: f64 = variant["learning rate"].parse().unwrap();
+            assert!((1e-4..=1e-2).contains(&lr));
+            let seed: i64 = variant["seed"].parse().unwrap();
+            assert!((0..=9).contains(&seed));
+        }
+
+        // the same seed should give the same trials
+        assert_eq!(sweep.variants(), variants);
+        assert_ne!(Sweep { seed: 43, ..sweep }.variants(), variants);
+
+        // the invalid spaces
+        for spec in &["epoch", "=1,2", "epoch=1,,2"] {
+            assert!(Space::parse_grid(spec).is_err());
+        }
+        for spec in &[
+            "lr=normal(0,1)",
+            "lr=uniform(1)",
+            "lr=uniform(2,1)",
+            "lr=loguniform(0,1)",
+            "lr=uniform(0,1",
+        ] {
+            assert!(Space::parse_random(spec).is_err());
+        }
+
+        // the ranges wider than i64
+        let wide = Sweep {
+            spaces: vec![Space::parse_random(&format!(
+                "seed=randint({},{})",
+                i64::MIN,
+                i64::MAX,
+            ))
+            .unwrap()],
+            trials: 4,
+            seed: 42,
+        };
+        for variant in wide.variants() {
+            assert!(variant["seed"].parse::<i64>().is_ok());
+        }
+
+        // building the trials
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let mut summary = SweepSummary {
+            exec: "DummyImageClassification".to_string(),
+            seed: sweep.seed,
+            trials: vec![],
+        };
+        for (index, variant) in variants.into_iter().take(3).enumerate() {
+            let args = root.get("DummyImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "LeNet5").unwrap();
+            for name in &["batch size", "epoch"] {
+                args.set(name, &variant[*name]).unwrap();
+            }
+
+            let mut trial = Trial::new(index, variant);
+            trial.program = Some(args.build_uncompacted().unwrap().digest().unwrap());
+            summary.trials.push(trial);
+        }
+        assert_ne!(summary.trials[0].program, summary.trials[2].program);
+
+        let path = std::env::temp_dir().join("n3-test-sweep.toml");
+        summary.save(&path).unwrap();
+        assert_eq!(SweepSummary::load(&path).unwrap(), summary);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_overrides() {
+        use crate::error::{BuildError, Error, ExecError, GraphError};
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let mut build = |overrides: &[&str]| {
+            let mut args = root.get("DummyImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "LeNet5").unwrap();
+            args.set("epoch", "1").unwrap();
+            args.set("batch size", "10").unwrap();
+            for spec in overrides {
+                args.add_override(spec.parse()?);
+            }
+            args.build_uncompacted()
+        };
+
+        let program = build(&[
+            "model.K=7",
+            "optimizer.learning rate=0.01",
+            "model.MyConv.Conv2D.stride=1",
+        ])
+        .unwrap();
+        let value = |code: &crate::Code, name: &str| {
+            let var = code.data().graph.variables[name].borrow();
+            format!("{:?}", var.value.as_ref().unwrap())
+        };
+
+        let model = &program.nodes["model"];
+        assert_eq!(value(model, "kernel size"), "7");
+        assert_eq!(value(&program.nodes["optimizer"], "learning rate"), "0.01");
+        for my_conv in &model.as_node().tensor_graph[1..3] {
+            let conv = &my_conv.as_node().tensor_graph[0];
+            assert_eq!(value(conv, "kernel size"), "kernel size=7");
+            assert_eq!(value(conv, "stride"), "1");
+        }
+
+        // the shortcuts
+        let program_shortcut =
+            build(&["model.K=7", "optimizer.lr=0.01", "model.MyConv.Conv2D.S=1"]);
+        assert_eq!(program_shortcut.unwrap(), program);
+
+        // the mismatched types
+        assert!(matches!(
+            build(&["model.K=yes"]),
+            Err(Error::BuildError(BuildError::GraphError(
+                GraphError::MismatchedType {
+                    given: Some(ast::LetType::Bool),
+                    ..
+                }
+            ))),
+        ));
+
+        // the unknown paths
+        for (spec, path) in &[
+            ("model.X=1", "model.X"),
+            ("modle.K=1", "modle"),
+            ("model.Foo.stride=1", "model.Foo"),
+            ("model.MyConv.Relu.stride=1", "model.MyConv.Relu.stride"),
+        ] {
+            match build(&[spec]) {
+                Err(Error::BuildError(BuildError::GraphError(GraphError::NoSuchVariable {
+                    name,
+                    ..
+                }))) => assert_eq!(&name, path),
+                _ => panic!("the path should be unknown: {}", spec),
+            }
+        }
+
+        assert!(matches!(
+            build(&["model=LeNet5"]),
+            Err(Error::ExecError(ExecError::InvalidOverride { .. })),
+        ));
+    }
+
+    #[test]
+    fn test_deferred_guard() {
+        use crate::error::{BuildError, Error, GraphNodeError};
+
+        let model = "
+node GuardedNet:
+    let use_dropout = bool *
+
+    0. Input                = 1, 28, 28
+    1. ToLinear
+    2. Linear               = 10
+    3. Dropout ? use_dropout
+";
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+        root.add_source("GuardedNet".to_string(), model.to_string());
+
+        let mut build = |overrides: &[&str]| {
+            let mut args = root.get("DummyImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "GuardedNet").unwrap();
+            args.set("epoch", "1").unwrap();
+            args.set("batch size", "10").unwrap();
+            for spec in overrides {
+                args.add_override(spec.parse().unwrap());
+            }
+            args.build_uncompacted()
+        };
+        let names = |program: &Program| -> Vec<String> {
+            program.nodes["model"]
+                .as_node()
+                .tensor_graph
+                .iter()
+                .map(|x| x.data().name.clone())
+                .collect()
+        };
+
+        // the guard should be given by the overrides
+        assert!(matches!(
+            build(&[]),
+            Err(Error::BuildError(BuildError::GraphNodeError(
+                GraphNodeError::UnresolvedGuard { .. }
+            ))),
+        ));
+
+        let program = build(&["model.use_dropout=yes"]).unwrap();
+        assert_eq!(
+            names(&program),
+            ["AssertShape", "ToLinear", "Linear", "Dropout"]
+        );
+
+        let program = build(&["model.use_dropout=no"]).unwrap();
+        assert_eq!(names(&program), ["AssertShape", "ToLinear", "Linear"]);
+    }
+
+    #[test]
+    fn test_constraints() {
+        use crate::error::{BuildError, Error, GraphError};
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let mut root = new_root(envs);
+
+        let mut args = root.get("ConstrainedImageClassification").unwrap();
+        let is_violated = |result: crate::Result<()>| {
+            matches!(
+                result,
+                Err(Error::BuildError(BuildError::GraphError(
+                    GraphError::ViolatedConstraint { .. }
+                ))),
+            )
+        };
+
+        // the ranges
+        let error = args.set("epoch", "0").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "build error: the variable `epoch` should be in `1..`, but given `0`"
+        );
+        assert!(is_violated(args.set("batch size", "-1")));
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+        assert!(is_violated(args.set("zoom in", "2")));
+        args.set("zoom in", "0.5").unwrap();
+
+        // the choices
+        assert!(is_violated(args.set("data", "Cifar10")));
+        args.set("data", "mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+
+        // the nested variables
+        for spec in &["optimizer.lr=0", "optimizer.lr=1", "optimizer.lr=-0.5"] {
+            args.add_override(spec.parse().unwrap());
+            assert!(is_violated(args.build_uncompacted().map(|_| ())));
+            args = root.get("ConstrainedImageClassification").unwrap();
+            args.set("data", "Mnist").unwrap();
+            args.set("model", "LeNet5").unwrap();
+            args.set("epoch", "1").unwrap();
+            args.set("batch size", "10").unwrap();
+        }
+        args.add_override("optimizer.lr=0.5".parse().unwrap());
+        args.build_uncompacted().unwrap();
+    }
+
+    #[test]
+    fn test_node_names_by_type() {
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let root = new_root(envs);
+
+        let data = root.node_names_by_type(ast::LetNodeType::Extern(ast::ExternNodeType::Data));
+        assert_eq!(data, ["Mnist"]);
+        let optim = root.node_names_by_type(ast::LetNodeType::Extern(ast::ExternNodeType::Optim));
+        assert_eq!(optim, ["Adam"]);
+
+        let models = root.node_names_by_type(ast::LetNodeType::Default);
+        assert!(models.contains(&"LeNet5".to_string()));
+        assert!(models.contains(&"Conv2D".to_string()));
+        assert!(!models.contains(&"Mnist".to_string()));
+        assert!(!models.contains(&"Adam".to_string()));
+
+        let execs = root.exec_names();
+        assert!(execs.contains(&"DummyImageClassification".to_string()));
+        assert!(!models.iter().any(|x| execs.contains(x)));
+    }
+
+    #[test]
+    fn test_parse_node() {
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let root = new_root(envs);
+
+        let node = root.parse_node("LeNet5").unwrap().node;
+        assert_eq!(node.ty, ast::NodeType::Default);
+        assert_eq!(node.graph["kernel size"].shortcut.as_deref(), Some("K"));
+        assert!(node.children.contains_key("MyConv"));
+        assert!(node.withs.contains_key("Conv2D"));
+        assert!(node.tensor_graph.contains_key(&0));
+
+        assert!(root.parse_node("NoSuchNode").is_err());
+    }
+
+    #[test]
+    fn test_build_ic_shared() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let root = new_root(envs).share();
+        assert_send_sync(&root);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let root = root.clone();
+                std::thread::spawn(move || {
+                    let mut root = root.spawn().unwrap();
+
+                    let args = root.get("DummyImageClassification").unwrap();
+                    args.set("data", "Mnist").unwrap();
+                    args.set("model", "LeNet5").unwrap();
+                    args.set("epoch", "1").unwrap();
+                    args.set("batch size", "10").unwrap();
+                    args.build_with_env().unwrap()
+                })
+            })
+            .collect();
+
+        let binaries: Vec<_> = threads.into_iter().map(|x| x.join().unwrap()).collect();
+        assert!(binaries.windows(2).all(|x| x[0] == x[1]));
+    }
+
+    #[test]
+    fn test_find_checkpoint() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join("n3-test-checkpoints");
+        let _ = fs::remove_dir_all(&dir);
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, &dir.display().to_string()).unwrap();
+
+        // a work with a single checkpoint
+        let path = envs.checkpoint_path("123", "abc");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+        assert_eq!(envs.find_checkpoint("123", "abc").unwrap(), path);
+        assert_eq!(envs.find_checkpoint("123", "def").unwrap(), path);
+        assert_eq!(
+            envs.find_checkpoint(&path.display().to_string(), "def")
+                .unwrap(),
+            path.canonicalize().unwrap()
+        );
+        // the relative paths are resolved from the current directory
+        assert!(envs
+            .find_checkpoint("Cargo.toml", "def")
+            .unwrap()
+            .is_absolute());
+
+        // a work with several checkpoints
+        let path_other = envs.checkpoint_path("123", "def");
+        fs::write(&path_other, "").unwrap();
+        assert_eq!(envs.find_checkpoint("123", "def").unwrap(), path_other);
+        assert!(matches!(
+            envs.find_checkpoint("123", "ghi"),
+            Err(crate::Error::ExecError(
+                crate::error::ExecError::NoSuchCheckpoint { .. }
+            ))
+        ));
+
+        // no such work
+        assert!(envs.find_checkpoint("456", "abc").is_err());
+    }
+
+    #[test]
+    fn test_library_paths() {
+        use std::fs;
+
+        let dir = std::env::temp_dir().join("n3-test-library-paths");
+        let dir_a = dir.join("a");
+        let dir_b = dir.join("b");
+        fs::create_dir_all(&dir_a).unwrap();
+        fs::create_dir_all(&dir_b).unwrap();
+
+        let model = "
+node MyNode:
+    0. Input    = 10
+    1. Linear   = 20
+";
+        fs::write(dir_a.join("my_node.n3"), model).unwrap();
+        fs::write(dir_b.join("my_node.n3"), model).unwrap();
+        fs::write(
+            dir_b.join("my_block.n3"),
+            model.replace("MyNode", "MyBlock"),
+        )
+        .unwrap();
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "tests/data/").unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let paths = [dir_a.display().to_string(), dir_b.display().to_string()];
+        envs.set(dirs::N3_PATH, &paths.join(QUERY_SPLIT_1)).unwrap();
+        let root = new_root(envs);
+
+        let path_a = dir_a.join("my_node.n3").display().to_string();
+        let path_b = dir_b.join("my_node.n3").display().to_string();
+
+        // the first root has the highest priority
+        let origins = root.node_origins();
+        assert_eq!(origins["MyNode"], path_a);
+        assert_eq!(
+            origins["MyBlock"],
+            dir_b.join("my_block.n3").display().to_string()
+        );
+        assert!(origins["Linear"].ends_with("linear.n3"));
+
+        let duplicated = root.duplicated_nodes();
+        assert_eq!(duplicated.len(), 1);
+        assert_eq!(duplicated["MyNode"], [path_a.clone(), path_b.clone()]);
+
+        // the shadowed node should be used after the other one is removed
+        fs::remove_file(&path_a).unwrap();
+        root.reload().unwrap();
+        assert_eq!(root.node_origins()["MyNode"], path_b);
+        assert!(root.duplicated_nodes().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use inflector::Inflector;
+
+use super::var::Vars;
+use crate::ast;
+use crate::error::{ExecError, GraphError, Result};
+use crate::graph::{check_constraint, RefGraph};
+use crate::nodes::NodeIR;
+use crate::tensor::TensorNode;
+
+/// A value of a nested node variable, given by the dotted path.
+///
+/// The path starts with the node variable of the exec, and may pass through the names of
+/// the inner nodes, e.g. `model.K`, `optimizer.learning rate` or `model.MyConv.Conv2D.padding`.
+/// The last one is the name or the shortcut of the variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Override {
+    pub path: Vec<String>,
+    pub value: String,
+}
+
+impl FromStr for Override {
+    type Err = crate::error::Error;
+
+    /// Parses the override, e.g. `model.K=7`.
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut tokens = spec.splitn(2, '=');
+        match (tokens.next(), tokens.next()) {
+            (Some(path), Some(value)) => Self::new(path, value),
+            _ => ExecError::InvalidOverride {
+                spec: spec.to_string(),
+            }
+            .into(),
+        }
+    }
+}
+
+impl Override {
+    pub fn new(path: &str, value: &str) -> Result<Self> {
+        let path: Vec<_> = path.split('.').map(|x| x.trim().to_string()).collect();
+        if path.len() < 2 || path.iter().any(|x| x.is_empty()) {
+            return ExecError::InvalidOverride {
+                spec: format!("{}={}", path.join("."), value),
+            }
+            .into();
+        }
+
+        Ok(Self {
+            path,
+            value: value.trim().to_string(),
+        })
+    }
+
+    /// Applies the value into the nodes, which are pruned from the exec.
+    pub(crate) fn apply(&self, nodes: &BTreeMap<String, NodeIR>) -> Result<()> {
+        let (name, path) = self.path.split_first().unwrap();
+        let (var_name, path) = path.split_last().unwrap();
+
+        // note: the node variables may be given in snake case
+        let node = nodes.get(name).or_else(|| {
+            let name = name.to_snake_case();
+            nodes
+                .iter()
+                .find(|(k, _)| k.to_snake_case() == name)
+                .map(|(_, node)| node)
+        });
+        let node = match node {
+            Some(node) => node,
+            None => return self.no_such_path(1, nodes.keys().cloned()),
+        };
+
+        // find the graphs of the inner nodes
+        let mut graphs = vec![&node.data.graph];
+        let mut children: Vec<&TensorNode> = node.tensor_graph.iter().collect();
+        for (depth, child_name) in path.iter().enumerate() {
+            let found: Vec<_> = children
+                .iter()
+                .filter(|x| x.name() == child_name)
+                .copied()
+                .collect();
+            if found.is_empty() {
+                let candidates = children.iter().map(|x| x.name().to_string());
+                return self.no_such_path(depth + 2, candidates);
+            }
+
+            graphs = found.iter().map(|x| x.get_graph()).collect();
+            children = found
+                .iter()
+                .filter_map(|x| match x {
+                    TensorNode::Node(node) => Some(node.tensor_graph.iter()),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+        }
+
+        for graph in graphs {
+            self.apply_to_graph(graph, var_name)?;
+        }
+        Ok(())
+    }
+
+    fn apply_to_graph(&self, graph: &RefGraph, var_name: &str) -> Result<()> {
+        let graph = graph.borrow();
+        let var = match graph.find(var_name) {
+            Some(var) => var,
+            None => {
+                return GraphError::NoSuchVariable {
+                    name: self.path.join("."),
+                    candidates: graph.candidates(),
+                }
+                .into()
+            }
+        };
+
+        let mut var = var.borrow_mut();
+        let name = self.path.join(".");
+        let expected = match &var.ty {
+            Some(ast::LetType::Node(_)) => {
+                return GraphError::UnexpectedNodeVariable { name }.into();
+            }
+            Some(ty) => ty.clone(),
+            None => match var.value.as_ref().and_then(|x| x.ty()) {
+                Some(ty) => ty,
+                None => guess_type(&self.value),
+            },
+        };
+
+        match Vars::encode(&name, self.value.clone(), Some(&expected)) {
+            Ok(value) => {
+                check_constraint(&var, &value)?;
+                var.value = Some(value);
+                Ok(())
+            }
+            Err(_) => GraphError::MismatchedType {
+                name,
+                expected,
+                given: Some(guess_type(&self.value)),
+            }
+            .into(),
+        }
+    }
+
+    fn no_such_path<T>(&self, depth: usize, candidates: impl Iterator<Item = String>) -> Result<T> {
+        GraphError::NoSuchVariable {
+            name: self.path[..depth].join("."),
+            candidates: candidates.collect(),
+        }
+        .into()
+    }
+}
+
+/// Guesses the type of the given string, to report the mismatched types.
+fn guess_type(value: &str) -> ast::LetType {
+    match value.to_lowercase().as_str() {
+        "yes" | "no" | "true" | "false" => ast::LetType::Bool,
+        _ if value.parse::<u64>().is_ok() => ast::LetType::UInt,
+        _ if value.parse::<i64>().is_ok() => ast::LetType::Int,
+        _ if value.parse::<f64>().is_ok() => ast::LetType::Real,
+        _ => ast::LetType::String,
+    }
+}
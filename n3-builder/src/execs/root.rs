@@ -1,15 +1,18 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::args::Args;
 use super::dirs::*;
 use super::program::Program;
 use super::var::GlobalVars;
-use crate::error::{ExecError, Result};
+use crate::cache::NodeSources;
+use crate::error::{ExecError, PackageError, Result};
 use crate::graph::ToValues;
 use crate::n3_std::trim_path;
-use crate::nodes::NodeRoot;
+use crate::nodes::{NodeRoot, SharedNodeRoot};
+use crate::package::{Lockfile, Packages, LOCK_FILE};
 
 use glob::glob;
 
@@ -31,6 +34,25 @@ pub struct ExecRoot {
     pub(super) env: GlobalVars,
 }
 
+/// The thread-safe handle of an `ExecRoot`, which spawns a new `ExecRoot` per build.
+#[derive(Clone, Debug)]
+pub struct SharedExecRoot {
+    node_root: SharedNodeRoot,
+    env: BTreeMap<String, String>,
+}
+
+impl SharedExecRoot {
+    pub fn spawn(&self) -> Result<ExecRoot> {
+        let root = ExecRoot {
+            node_root: self.node_root.spawn(),
+            env: GlobalVars::try_from_strings(&self.env)?,
+        };
+
+        root.reload()?;
+        Ok(root)
+    }
+}
+
 impl Deref for ExecRoot {
     type Target = NodeRoot;
 
@@ -43,17 +65,30 @@ impl ExecRoot {
     pub fn try_new(env: GlobalVars, config: ExecRootConfig) -> Result<Self> {
         let n3_source_root = env.get_string(N3_SOURCE_ROOT).ok();
 
-        let root = Self {
-            node_root: NodeRoot::new(n3_source_root.as_deref()),
-            env,
-        };
+        let mut node_root = NodeRoot::new(n3_source_root.as_deref());
+        node_root.set_cache_dir(env.root_dir().join(CACHE_DIR));
+
+        let root = Self { node_root, env };
 
         root.assert_root_dir(&config)?;
-        root.load_local_nodes()?;
+        root.load_nodes()?;
+        root.load_packages()?;
 
         Ok(root)
     }
 
+    /// Stores the built nodes into the given directory, instead of the cache directory of the root.
+    pub fn set_cache_dir(&mut self, dir: PathBuf) {
+        self.node_root.set_cache_dir(dir);
+    }
+
+    pub fn share(&self) -> SharedExecRoot {
+        SharedExecRoot {
+            node_root: self.node_root.share(),
+            env: self.env.to_strings(),
+        }
+    }
+
     pub fn get(&mut self, name: &str) -> Result<Args> {
         let ir = self.node_root.get_exec(name)?;
         let args = ir.args();
@@ -62,9 +97,20 @@ impl ExecRoot {
             root: self,
             ir,
             args,
+            overrides: vec![],
         })
     }
 
+    /// Picks up the nodes which have been added or removed since the last load.
+    ///
+    /// Note that the changed nodes are rebuilt automatically.
+    pub fn reload(&self) -> Result<()> {
+        self.node_root.remove_missing_paths();
+        self.node_root.load_std();
+        self.load_nodes()?;
+        self.load_packages()
+    }
+
     pub fn attach_env(&self, program: &mut Program) {
         program.env = Some(self.env.to_values());
     }
@@ -87,9 +133,43 @@ impl ExecRoot {
         }
     }
 
-    fn load_local_nodes(&self) -> Result<()> {
-        let path = self.env.root_dir().join(NODES_DIR);
-        let path = path.join("**/*.n3").display().to_string();
+    /// Loads the nodes of the library roots and the local root.
+    ///
+    /// The precedence is: local nodes > `N3_PATH` (from the first) > the standard library.
+    fn load_nodes(&self) -> Result<()> {
+        let libraries = self.env.library_dirs();
+        for (index, dir) in libraries.iter().enumerate() {
+            self.load_nodes_from(dir, libraries.len() - index)?;
+        }
+
+        let dir = self.env.root_dir().join(NODES_DIR);
+        self.load_nodes_from(&dir, NodeSources::PRIORITY_LOCAL)
+    }
+
+    /// Registers the installed packages, which should match the lockfile.
+    fn load_packages(&self) -> Result<()> {
+        let root_dir = self.env.root_dir();
+        let lock = Lockfile::load_or_default(&root_dir.join(LOCK_FILE))?;
+        let packages = Packages::new(root_dir.join(PACKAGES_DIR));
+
+        for locked in lock.packages {
+            let id = locked.id()?;
+            if packages.get(&id).map(|x| x.version) != Some(locked.version.clone()) {
+                return PackageError::NotInstalled {
+                    id: id.to_string(),
+                    version: locked.version,
+                }
+                .into();
+            }
+
+            let dir = packages.path(&id);
+            self.node_root.add_package(id, dir);
+        }
+        Ok(())
+    }
+
+    fn load_nodes_from(&self, dir: &Path, priority: usize) -> Result<()> {
+        let path = dir.join("**/*.n3").display().to_string();
 
         for path in glob(&path)? {
             let path = path?;
@@ -98,11 +178,12 @@ impl ExecRoot {
             let extern_path = path.with_extension("py");
             if extern_path.exists() {
                 let path_str = extern_path.display().to_string();
-                self.node_root.add_extern_path(name.clone(), path_str);
+                self.node_root
+                    .add_library_extern_path(name.clone(), path_str, priority);
             }
 
             let path_str = path.display().to_string();
-            self.node_root.add_source_path(name, path_str);
+            self.node_root.add_library_path(name, path_str, priority);
         }
         Ok(())
     }
@@ -132,11 +213,13 @@ impl ExecRoot {
     fn create_root_dir(path: &Path) -> Result<()> {
         fs::create_dir_all(path)?;
         for name in &[
+            Path::new(CACHE_DIR),
             Path::new(DATA_DIR),
             Path::new(LOGS_DIR),
             Path::new(MODELS_DIR),
             Path::new(NODES_DIR),
             &Path::new(NODES_DIR).join(NODES_USER_DIR),
+            Path::new(PACKAGES_DIR),
         ] {
             fs::create_dir(path.join(name))?;
         }
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ExecError, Result};
+
+/// A search space of an exec arg.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Space {
+    Grid(Vec<String>),
+    Choice(Vec<String>),
+    Uniform { low: f64, high: f64 },
+    LogUniform { low: f64, high: f64 },
+    RandInt { low: i64, high: i64 },
+}
+
+/// A hyperparameter sweep, which expands the grid spaces and samples the random ones.
+///
+/// Each point of the grid is repeated for the given number of trials,
+/// with the newly sampled random spaces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    pub spaces: Vec<(String, Space)>,
+    pub trials: usize,
+    pub seed: u64,
+}
+
+impl Space {
+    /// Parses the grid space, e.g. `batch size=32,64`.
+    pub fn parse_grid(spec: &str) -> Result<(String, Self)> {
+        let (name, values) = split_spec(spec)?;
+        Ok((name, Self::Grid(split_values(spec, values)?)))
+    }
+
+    /// Parses the random space, e.g. `learning rate=loguniform(1e-4,1e-2)`.
+    ///
+    /// The supported distributions are `uniform`, `loguniform`, `randint` and `choice`.
+    pub fn parse_random(spec: &str) -> Result<(String, Self)> {
+        let (name, value) = split_spec(spec)?;
+
+        let (dist, params) = value
+            .strip_suffix(')')
+            .and_then(|x| {
+                let mut tokens = x.splitn(2, '(');
+                Some((tokens.next()?.trim(), tokens.next()?))
+            })
+            .map_or_else(|| invalid_space(spec), Ok)?;
+        let params = split_values(spec, params)?;
+
+        let space = match dist {
+            "choice" => Self::Choice(params),
+            "uniform" => {
+                let (low, high) = parse_range(spec, &params)?;
+                Self::Uniform { low, high }
+            }
+            "loguniform" => {
+                let (low, high) = parse_range(spec, &params)?;
+                if low <= 0.0 {
+                    return invalid_space(spec);
+                }
+                Self::LogUniform { low, high }
+            }
+            "randint" => {
+                let (low, high) = parse_range(spec, &params)?;
+                Self::RandInt { low, high }
+            }
+            _ => return invalid_space(spec),
+        };
+        Ok((name, space))
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> String {
+        match self {
+            Self::Grid(values) | Self::Choice(values) => {
+                values[(rng.next_u64() % values.len() as u64) as usize].clone()
+            }
+            Self::Uniform { low, high } => (low + (high - low) * rng.next_f64()).to_string(),
+            Self::LogUniform { low, high } => {
+                let (low, high) = (low.ln(), high.ln());
+                (low + (high - low) * rng.next_f64()).exp().to_string()
+            }
+            Self::RandInt { low, high } => {
+                // note: the range may not fit in i64, e.g. `randint(i64::MIN, i64::MAX)`
+                let range = (*high as i128 - *low as i128 + 1) as u128;
+                (*low as i128 + (rng.next_u64() as u128 % range) as i128).to_string()
+            }
+        }
+    }
+}
+
+impl Sweep {
+    /// Returns the args of each trial, in the order of the grid.
+    pub fn variants(&self) -> Vec<BTreeMap<String, String>> {
+        let mut grid = vec![BTreeMap::new()];
+        for (name, space) in &self.spaces {
+            if let Space::Grid(values) = space {
+                grid = grid
+                    .into_iter()
+                    .flat_map(|args| {
+                        values.iter().map(move |value| {
+                            let mut args = args.clone();
+                            args.insert(name.clone(), value.clone());
+                            args
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        let mut rng = SplitMix64(self.seed);
+        grid.into_iter()
+            .flat_map(|args| (0..self.trials.max(1)).map(move |_| args.clone()))
+            .map(|mut args| {
+                for (name, space) in &self.spaces {
+                    if !matches!(space, Space::Grid(_)) {
+                        args.insert(name.clone(), space.sample(&mut rng));
+                    }
+                }
+                args
+            })
+            .collect()
+    }
+}
+
+/// The records of the trials in a sweep.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SweepSummary {
+    pub exec: String,
+    pub seed: u64,
+    #[serde(rename = "trial")]
+    pub trials: Vec<Trial>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trial {
+    pub index: usize,
+    pub status: TrialStatus,
+    pub program: Option<String>,
+    pub work: Option<String>,
+    pub error: Option<String>,
+    pub date_begin: Option<String>,
+    pub date_end: Option<String>,
+    // note: the tables should be placed after the values
+    pub args: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, f64>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl SweepSummary {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+impl Trial {
+    pub fn new(index: usize, args: BTreeMap<String, String>) -> Self {
+        Self {
+            index,
+            status: TrialStatus::Pending,
+            program: None,
+            work: None,
+            error: None,
+            date_begin: None,
+            date_end: None,
+            args,
+            metrics: Default::default(),
+        }
+    }
+}
+
+/// A tiny deterministic random number generator, to reproduce the sweeps with the seeds.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn split_spec(spec: &str) -> Result<(String, &str)> {
+    let mut tokens = spec.splitn(2, '=');
+    match (tokens.next(), tokens.next()) {
+        (Some(name), Some(value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim()))
+        }
+        _ => invalid_space(spec),
+    }
+}
+
+fn split_values(spec: &str, values: &str) -> Result<Vec<String>> {
+    let values: Vec<_> = values.split(',').map(|x| x.trim().to_string()).collect();
+    if values.iter().any(|x| x.is_empty()) {
+        invalid_space(spec)
+    } else {
+        Ok(values)
+    }
+}
+
+fn parse_range<T>(spec: &str, params: &[String]) -> Result<(T, T)>
+where
+    T: std::str::FromStr + PartialOrd,
+{
+    match params {
+        [low, high] => match (low.parse(), high.parse()) {
+            (Ok(low), Ok(high)) if low <= high => Ok((low, high)),
+            _ => invalid_space(spec),
+        },
+        _ => invalid_space(spec),
+    }
+}
+
+fn invalid_space<T>(spec: &str) -> Result<T> {
+    ExecError::InvalidSweepSpace {
+        spec: spec.to_string(),
+    }
+    .into()
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use inflector::Inflector;
+use serde::{Deserialize, Serialize};
+
+use super::core::{Vars, QUERY_SPLIT_1};
+use super::global::GlobalVars;
+use crate::ast;
+use crate::error::{ExecError, Result};
+use crate::execs::{Args, Override};
+
+/// The values of the variables, which are loaded from a config file.
+///
+/// The keys are matched to the variables in snake case, e.g. `batch_size` or `batch size`.
+/// The precedence is: defaults < config file < env < CLI.
+///
+/// The nested node variables are given in the `[set]` table by their dotted paths,
+/// e.g. `"model.K" = 7`, like the `--set` overrides.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub values: BTreeMap<String, ConfigValue>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, ConfigValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConfigValue {
+    Bool(bool),
+    Int(i64),
+    Real(f64),
+    String(String),
+    List(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    fn from_value(value: &ast::Value) -> Option<Self> {
+        match value {
+            ast::Value::Bool(value) => Some(Self::Bool(*value)),
+            ast::Value::UInt(value) => Some(Self::Int(*value as i64)),
+            ast::Value::Int(value) => Some(Self::Int(*value)),
+            ast::Value::Real(value) => Some(Self::Real(*value)),
+            ast::Value::String(value) | ast::Value::Node(value) => {
+                Some(Self::String(value.clone()))
+            }
+            ast::Value::List(values) => values
+                .iter()
+                .map(Self::from_value)
+                .collect::<Option<_>>()
+                .map(Self::List),
+            _ => None,
+        }
+    }
+
+    /// Encodes the value into the string, which can be parsed by `Vars::set`.
+    fn to_query(&self) -> String {
+        match self {
+            Self::Bool(value) => value.to_string(),
+            Self::Int(value) => value.to_string(),
+            Self::Real(value) => value.to_string(),
+            Self::String(value) => value.clone(),
+            Self::List(values) => values
+                .iter()
+                .map(|x| x.to_query())
+                .collect::<Vec<_>>()
+                .join(QUERY_SPLIT_1),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, with the format of its extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("yaml") | Some("yml") => Self::from_yaml(&text),
+            _ => ExecError::UnsupportedConfigFormat {
+                path: path.to_path_buf(),
+            }
+            .into(),
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(text)?)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Collects the effective values of the variables.
+    ///
+    /// Note that the empty values and the values with expressions are not collected.
+    pub fn from_vars(vars: &[&Vars]) -> Self {
+        let values = vars
+            .iter()
+            .flat_map(|vars| &vars.inner)
+            .filter_map(|(name, var)| {
+                let value = ConfigValue::from_value(var.borrow().value.as_ref()?)?;
+                Some((name.to_snake_case(), value))
+            })
+            .collect();
+        Self {
+            values,
+            set: Default::default(),
+        }
+    }
+
+    /// Collects the overrides of the nested node variables, into the `[set]` table.
+    pub fn with_overrides(mut self, overrides: &[Override]) -> Self {
+        for value in overrides {
+            self.set.insert(
+                value.path.join("."),
+                ConfigValue::String(value.value.clone()),
+            );
+        }
+        self
+    }
+
+    /// Applies the values into the env.
+    ///
+    /// The values which are given by the environment variables are not overridden,
+    /// and the other keys are skipped.
+    pub fn apply_env(&self, env: &GlobalVars) -> Result<()> {
+        for (key, value) in &self.values {
+            if let Some(name) = env.find_name(key) {
+                if !env.is_from_env(name) {
+                    env.set(name, &value.to_query())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the values into the args, and fails on the keys which are neither in the args nor the env.
+    ///
+    /// The `[set]` table is added as the overrides, so the later ones such as `--set` take precedence.
+    pub fn apply_args(&self, env: &GlobalVars, args: &mut Args) -> Result<()> {
+        for (key, value) in &self.values {
+            match args.find_name(key) {
+                Some(name) => args.set(name, &value.to_query())?,
+                None if env.find_name(key).is_some() => continue,
+                None => return args.get(key).map(|_| ()),
+            }
+        }
+        for (path, value) in &self.set {
+            args.set_nested(path, &value.to_query())?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::ast;
 use crate::error::{GraphError, Result};
-use crate::graph::{ToValues, Values, Variables};
+use crate::graph::{check_constraint, ToValues, Values, Variables};
 
 use inflector::Inflector;
 
@@ -62,12 +62,32 @@ impl Vars {
         self.inner.get(name).ok_or_else(|| {
             GraphError::NoSuchVariable {
                 name: name.to_string(),
-                candidates: self.inner.keys().cloned().collect(),
+                candidates: self
+                    .inner
+                    .iter()
+                    .flat_map(|(name, var)| {
+                        let shortcut = var.borrow().shortcut.clone();
+                        std::iter::once(name.clone()).chain(shortcut)
+                    })
+                    .collect(),
             }
             .into()
         })
     }
 
+    /// Finds the name of the variable, which may be given in snake case.
+    pub fn find_name(&self, key: &str) -> Option<&str> {
+        if let Some((name, _)) = self.inner.get_key_value(key) {
+            return Some(name);
+        }
+
+        let key = key.to_snake_case();
+        self.inner
+            .keys()
+            .find(|name| name.to_snake_case() == key)
+            .map(|name| name.as_str())
+    }
+
     pub fn try_get_checked(
         &self,
         name: &str,
@@ -156,7 +176,9 @@ impl Vars {
 
     pub fn set(&self, name: &str, value: &str) -> Result<()> {
         let mut var = self.get(name)?.borrow_mut();
-        var.value = Some(Self::encode(name, value.to_string(), var.ty.as_ref())?);
+        let value = Self::encode(name, value.to_string(), var.ty.as_ref())?;
+        check_constraint(&var, &value)?;
+        var.value = Some(value);
         Ok(())
     }
 
@@ -176,11 +198,16 @@ impl Vars {
             .into();
         }
 
+        check_constraint(&var, &value)?;
         var.value = Some(value);
         Ok(())
     }
 
-    fn encode(name: &str, value: String, ty: Option<&ast::LetType>) -> Result<ast::Value> {
+    pub(crate) fn encode(
+        name: &str,
+        value: String,
+        ty: Option<&ast::LetType>,
+    ) -> Result<ast::Value> {
         match ty {
             Some(ast::LetType::List(ty)) => Ok(ast::Value::List(
                 value
@@ -32,6 +32,11 @@ impl EnvVars {
         })
     }
 
+    /// Returns whether the variable is given by the environment variable.
+    pub fn is_from_env(&self, key: &str) -> bool {
+        Self::load_from_env(key).is_some()
+    }
+
     fn load_from_env(key: &str) -> Option<String> {
         let key = format!("N3_{}", key.to_screaming_snake_case());
         std::env::var(&key).ok()
@@ -1,12 +1,15 @@
+use std::collections::BTreeMap;
+use std::fs;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use lazy_static::lazy_static;
 
 use super::super::dirs::*;
-use super::core::Query;
+use super::core::{Query, QUERY_SPLIT_1};
 use super::env::EnvVars;
 use crate::ast;
+use crate::error::{ExecError, Result};
 use crate::graph::{ToValues, Values};
 
 #[derive(Clone, Debug)]
@@ -30,6 +33,20 @@ lazy_static! {
             value: None,
             fn_value: Some(GlobalVars::default_home_dir),
         },
+        Query {
+            name: N3_PATH,
+            description: "The n3 library paths, from the highest priority",
+            ty: ast::LetType::List(Box::new(ast::LetType::String)),
+            value: None,
+            fn_value: Some(GlobalVars::default_path),
+        },
+        Query {
+            name: N3_REGISTRY,
+            description: "The n3 package registry's path",
+            ty: ast::LetType::String,
+            value: None,
+            fn_value: Some(GlobalVars::default_registry_dir),
+        },
         Query {
             name: N3_MACHINES,
             description: "The n3 machines",
@@ -42,8 +59,17 @@ lazy_static! {
 
 impl Default for GlobalVars {
     fn default() -> Self {
+        let mut query = QUERY.to_vec();
+
+        // note: the library paths are separated like the system's `PATH`
+        for entry in &mut query {
+            if entry.name == N3_PATH {
+                entry.value = Self::load_path_from_env();
+            }
+        }
+
         Self {
-            inner: EnvVars::load(QUERY.to_vec()).unwrap(),
+            inner: EnvVars::load(query).unwrap(),
         }
     }
 }
@@ -61,6 +87,93 @@ impl GlobalVars {
         PathBuf::from(self.get_string(N3_ROOT).unwrap())
     }
 
+    pub fn registry_dir(&self) -> PathBuf {
+        PathBuf::from(self.get_string(N3_REGISTRY).unwrap())
+    }
+
+    /// Returns the checkpoint which the work trains from the program.
+    pub fn checkpoint_path(&self, work: &str, digest: &str) -> PathBuf {
+        self.root_dir()
+            .join(MODELS_DIR)
+            .join(work)
+            .join(format!("{}.{}", digest, CHECKPOINT_EXT))
+    }
+
+    /// Finds the checkpoint by the path, or by the work id.
+    ///
+    /// If the work has several checkpoints, the one trained from the program is used.
+    pub fn find_checkpoint(&self, from: &str, digest: &str) -> Result<PathBuf> {
+        let path = Path::new(from);
+        if path.is_file() {
+            // note: the works may run in the other directories
+            return Ok(path.canonicalize()?);
+        }
+
+        let path = self.checkpoint_path(from, digest);
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        let dir = self.root_dir().join(MODELS_DIR).join(from);
+        let checkpoints: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|x| x.ok())
+            .map(|x| x.path())
+            .filter(|x| x.extension().and_then(|x| x.to_str()) == Some(CHECKPOINT_EXT))
+            .collect();
+        match checkpoints.as_slice() {
+            [path] => Ok(path.clone()),
+            _ => ExecError::NoSuchCheckpoint {
+                from: from.to_string(),
+            }
+            .into(),
+        }
+    }
+
+    /// Returns the library roots, from the highest priority.
+    pub fn library_dirs(&self) -> Vec<PathBuf> {
+        self.get_string_list(N3_PATH)
+            .map(|x| x.into_iter().map(PathBuf::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Encodes the given values into the strings, which can be sent to the other threads.
+    pub(crate) fn to_strings(&self) -> BTreeMap<String, String> {
+        QUERY
+            .iter()
+            .filter_map(|query| {
+                let value = match &query.ty {
+                    ast::LetType::List(_) => self
+                        .get_string_list(query.name)
+                        .map(|x| x.join(QUERY_SPLIT_1)),
+                    _ => self.get_string(query.name),
+                };
+                value.ok().map(|x| (query.name.to_string(), x))
+            })
+            .collect()
+    }
+
+    pub(crate) fn try_from_strings(values: &BTreeMap<String, String>) -> Result<Self> {
+        let vars = Self::default();
+        for (name, value) in values {
+            vars.set(name, value)?;
+        }
+        Ok(vars)
+    }
+
+    fn load_path_from_env() -> Option<String> {
+        let paths = std::env::var_os("N3_PATH")?;
+        let paths: Vec<_> = std::env::split_paths(&paths)
+            .map(|x| x.display().to_string())
+            .collect();
+        Some(paths.join(QUERY_SPLIT_1))
+    }
+
+    pub(crate) fn default_path() -> Option<String> {
+        Some(String::new())
+    }
+
     pub(crate) fn default_home_dir() -> Option<String> {
         dirs::home_dir()
             .map(|mut dir| {
@@ -70,6 +183,10 @@ impl GlobalVars {
             .map(|x| x.display().to_string())
     }
 
+    pub(crate) fn default_registry_dir() -> Option<String> {
+        Self::default_home_dir().map(|x| format!("{}/registry", x))
+    }
+
     #[cfg(feature = "pip")]
     pub(crate) fn get_n3_source_root() -> PathBuf {
         let output = std::process::Command::new("python")
@@ -1,7 +1,9 @@
+mod config;
 mod core;
 mod env;
 mod global;
 
+pub use self::config::{Config, ConfigValue};
 pub use self::core::{Vars, QUERY_SPLIT_1};
 pub use self::env::EnvVars;
 pub use self::global::GlobalVars;
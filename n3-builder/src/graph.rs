@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::rc::Rc;
 
 use crate::ast;
@@ -42,7 +43,7 @@ impl Graph {
         graph
     }
 
-    pub fn try_with_variables<I>(id: u64, variables: I, is_exec: bool) -> Result<Self>
+    pub fn try_with_variables<I>(id: u64, variables: I, allow_nodes: bool) -> Result<Self>
     where
         I: IntoIterator<Item = (String, ast::NodeLet)>,
     {
@@ -50,24 +51,25 @@ impl Graph {
             .into_iter()
             .map(|(k, v)| {
                 // filter nodes from variables
-                if !is_exec {
+                if !allow_nodes {
                     if let ast::LetType::Node(_) = v.ty {
                         return GraphError::UnexpectedNodeVariable { name: k }.into();
                     }
                 }
-                Ok((
-                    k,
-                    ast::Variable {
-                        id: Some(id),
-                        id_old: Some(id),
-                        name: v.name,
-                        shortcut: v.shortcut,
-                        description: None,
-                        ty: Some(v.ty),
-                        value: v.value,
-                    }
-                    .into(),
-                ))
+                let var = ast::Variable {
+                    id: Some(id),
+                    id_old: Some(id),
+                    name: v.name,
+                    shortcut: v.shortcut,
+                    description: None,
+                    ty: Some(v.ty),
+                    value: v.value,
+                    constraint: v.constraint,
+                };
+                if let Some(value) = &var.value {
+                    check_constraint(&var, value)?;
+                }
+                Ok((k, var.into()))
             })
             .collect::<Result<_>>()?;
 
@@ -115,11 +117,15 @@ impl Graph {
 
         for (name, v) in variables.into_iter() {
             if let Some(var) = self_variables.get(&name) {
-                var.borrow_mut().value = v;
+                let mut var = var.borrow_mut();
+                if let Some(value) = &v {
+                    check_constraint(&var, value)?;
+                }
+                var.value = v;
             } else {
                 return GraphError::NoSuchVariable {
                     name,
-                    candidates: self_variables.keys().cloned().collect(),
+                    candidates: self.candidates(),
                 }
                 .into();
             }
@@ -127,16 +133,40 @@ impl Graph {
         Ok(())
     }
 
+    pub fn get_node_variable(&self, shortcut: &str) -> Option<&ast::RefVariable> {
+        self.shortcuts.get(shortcut).filter(|x| x.borrow().is_node())
+    }
+
+    pub fn prune_nodes(&mut self) {
+        self.variables.retain(|_, v| !v.borrow().is_node());
+        self.shortcuts.retain(|_, v| !v.borrow().is_node());
+    }
+
     pub fn get(&self, name: &str) -> Result<&ast::RefVariable> {
         self.variables.get(name).ok_or_else(|| {
             GraphError::NoSuchVariable {
                 name: name.to_string(),
-                candidates: self.variables.keys().cloned().collect(),
+                candidates: self.candidates(),
             }
             .into()
         })
     }
 
+    /// Finds the variable by its name or shortcut.
+    pub fn find(&self, name: &str) -> Option<&ast::RefVariable> {
+        self.variables
+            .get(name)
+            .or_else(|| self.shortcuts.get(name))
+    }
+
+    pub(crate) fn candidates(&self) -> BTreeSet<String> {
+        self.variables
+            .keys()
+            .chain(self.shortcuts.keys())
+            .cloned()
+            .collect()
+    }
+
     fn build(&mut self) -> Result<()> {
         let shortcuts_map = self
             .variables
@@ -221,6 +251,21 @@ impl Graph {
         self.variables
     }
 
+    pub fn from_table(table: Table) -> Self {
+        Self {
+            id: table.id,
+            shortcuts: to_shortcuts(&table.variables),
+            variables: table.variables,
+        }
+    }
+
+    pub fn to_table(&self) -> Table {
+        Table {
+            id: self.id,
+            variables: self.variables.clone(),
+        }
+    }
+
     pub fn into_table(self) -> Table {
         Table {
             id: self.id,
@@ -294,3 +339,16 @@ fn to_shortcuts(variables: &Variables) -> Variables {
         })
         .collect()
 }
+
+/// Tests whether the value satisfies the constraint of the variable, if any.
+pub(crate) fn check_constraint(var: &ast::Variable, value: &ast::Value) -> Result<()> {
+    match &var.constraint {
+        Some(constraint) if !constraint.test(value) => GraphError::ViolatedConstraint {
+            name: var.name.clone(),
+            value: value.clone(),
+            constraint: constraint.clone(),
+        }
+        .into(),
+        _ => Ok(()),
+    }
+}
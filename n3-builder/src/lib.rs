@@ -12,8 +12,11 @@ mod error;
 mod execs;
 mod externs;
 mod graph;
+mod lint;
 mod nodes;
+pub mod package;
 mod seed;
+mod suggest;
 mod tensor;
 mod variable;
 
@@ -22,10 +25,12 @@ pub use n3_parser::ast;
 pub use self::code::{Code, CodeData, CodeType};
 pub use self::error::{Error, Result};
 pub use self::execs::{
-    dirs, Args, ExecRoot, ExecRootConfig, GlobalVars, Program, Vars, QUERY_SPLIT_1,
+    dirs, Args, Config, ConfigValue, ExecRoot, ExecRootConfig, GlobalVars, Override, Program,
+    SharedExecRoot, Space, Sweep, SweepSummary, Trial, TrialStatus, Vars, QUERY_SPLIT_1,
 };
 pub use self::externs::{ExternCode, PythonScripts};
 pub use self::graph::ToValues;
+pub use self::lint::LintWarning;
 pub use self::nodes::NodeCode;
 
 use n3_parser::Parser;
@@ -54,7 +59,8 @@ mod tests_recon {
     #[test]
     fn test_all_externs() {
         let path = std::path::PathBuf::from("../n3-torch/ffi/python/n3");
-        for source in super::n3_std::get_sources(&path).values() {
+        for source_path in super::n3_std::get_source_paths(&path).values() {
+            let source = fs::read_to_string(source_path).unwrap();
             recon(&source);
         }
     }
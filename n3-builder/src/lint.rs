@@ -0,0 +1,391 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::ast;
+use crate::error::Result;
+use crate::nodes::NodeRoot;
+
+#[derive(Debug, PartialEq)]
+pub enum LintWarning {
+    UnusedVariable { node: String, name: String },
+    UnusedWith { node: String, name: String },
+    UnknownWithKey { node: String, with: String, name: String },
+    ShadowedNode { node: String, name: String },
+    MismatchedId { node: String, expected: u64, given: u64 },
+    UnboundDim { node: String, name: String },
+    DuplicatedNode { name: String, path: String, shadowed: Vec<String> },
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnusedVariable { node, name } => {
+                write!(f, "unused variable `{}` in node `{}`", name, node)
+            }
+            Self::UnusedWith { node, name } => write!(
+                f,
+                "`with {}` in node `{}` redefines a node that is never called",
+                name, node
+            ),
+            Self::UnknownWithKey { node, with, name } => write!(
+                f,
+                "`with {}` in node `{}` sets the unknown variable `{}`",
+                with, node, name
+            ),
+            Self::ShadowedNode { node, name } => write!(
+                f,
+                "the child node `{}` in node `{}` shadows another node",
+                name, node
+            ),
+            Self::MismatchedId {
+                node,
+                expected,
+                given,
+            } => write!(
+                f,
+                "graph id {} in node `{}` should be {}",
+                given, node, expected
+            ),
+            Self::UnboundDim { node, name } => write!(
+                f,
+                "dim `{}` in node `{}` is never bound by the tensor graph",
+                name, node
+            ),
+            Self::DuplicatedNode {
+                name,
+                path,
+                shadowed,
+            } => write!(
+                f,
+                "node `{}` in \"{}\" shadows the ones in \"{}\"",
+                name,
+                path,
+                shadowed.join("\", \""),
+            ),
+        }
+    }
+}
+
+impl NodeRoot {
+    /// Reports the suspicious parts of the node without building it.
+    pub fn lint(&self, name: &str) -> Result<Vec<LintWarning>> {
+        let source = self.get_source(name)?;
+        let file = self.parser.parse_file(&source)?;
+
+        let mut lint = Lint {
+            root: self,
+            names: self.node_names(),
+            warnings: vec![],
+        };
+        lint.lint_node(&file.node, &[], &[]);
+        Ok(lint.warnings)
+    }
+
+    /// Reports the nodes which are defined in several roots.
+    pub fn lint_roots(&self) -> Vec<LintWarning> {
+        self.duplicated_nodes()
+            .into_iter()
+            .map(|(name, mut paths)| LintWarning::DuplicatedNode {
+                name,
+                path: paths.remove(0),
+                shadowed: paths,
+            })
+            .collect()
+    }
+
+    /// Tests whether the node can be built.
+    pub fn check(&self, name: &str) -> Result<()> {
+        self.get_any(name).map(|_| ())
+    }
+}
+
+struct Lint<'a> {
+    root: &'a NodeRoot,
+    names: BTreeSet<String>,
+    warnings: Vec<LintWarning>,
+}
+
+impl<'a> Lint<'a> {
+    fn lint_node(&mut self, node: &ast::Node, parent: &[String], scopes: &[&ast::Node]) {
+        let mut path = parent.to_vec();
+        path.push(node.name.clone());
+        let name = path.join("::");
+
+        // Step 1. collect the names used in the node
+        let mut uses = BTreeSet::new();
+        let mut shapes = BTreeSet::new();
+        let mut calls = BTreeSet::new();
+        collect_node(node, &mut uses, &mut shapes, &mut calls);
+
+        // Step 2. test the variables
+        for var in node.graph.values() {
+            let is_used = |names: &BTreeSet<String>| {
+                names.contains(&var.name)
+                    || var.shortcut.iter().any(|x| names.contains(x))
+            };
+
+            // note: the extern & exec nodes pass their variables to the scripts
+            if node.ty == ast::NodeType::Default && !is_used(&uses) {
+                self.warnings.push(LintWarning::UnusedVariable {
+                    node: name.clone(),
+                    name: var.name.clone(),
+                });
+            } else if var.ty == ast::LetType::Dim && !is_used(&shapes) {
+                self.warnings.push(LintWarning::UnboundDim {
+                    node: name.clone(),
+                    name: var.name.clone(),
+                });
+            }
+        }
+
+        // Step 3. test the withs
+        for (with, w) in &node.withs {
+            if !calls.contains(with) {
+                self.warnings.push(LintWarning::UnusedWith {
+                    node: name.clone(),
+                    name: with.clone(),
+                });
+            }
+
+            // note: the unknown nodes are not tested
+            let keys = match self.get_variables(with, node, scopes) {
+                Some(keys) => keys,
+                None => continue,
+            };
+            for key in w.graph.keys() {
+                if !keys.contains(key) {
+                    self.warnings.push(LintWarning::UnknownWithKey {
+                        node: name.clone(),
+                        with: with.clone(),
+                        name: key.clone(),
+                    });
+                }
+            }
+        }
+
+        // Step 4. test the graph ids
+        let first_id = match node.ty {
+            ast::NodeType::Default => node.tensor_graph.keys().next().map(|&x| x.min(1)),
+            ast::NodeType::Exec => Some(1),
+            ast::NodeType::Extern(_) => None,
+        };
+        if let Some(mut expected) = first_id {
+            for &given in node.tensor_graph.keys() {
+                if given != expected {
+                    self.warnings.push(LintWarning::MismatchedId {
+                        node: name.clone(),
+                        expected,
+                        given,
+                    });
+                }
+                expected = given + 1;
+            }
+        }
+
+        // Step 5. test the children
+        let mut child_scopes = scopes.to_vec();
+        child_scopes.extend(node.children.values());
+        for (child_name, child) in &node.children {
+            if self.names.contains(child_name) || scopes.iter().any(|x| &x.name == child_name) {
+                self.warnings.push(LintWarning::ShadowedNode {
+                    node: name.clone(),
+                    name: child_name.clone(),
+                });
+            }
+            self.lint_node(child, &path, &child_scopes);
+        }
+    }
+
+    /// Returns the names and shortcuts of the variables of the node which is visible in the scope.
+    fn get_variables(
+        &self,
+        name: &str,
+        node: &ast::Node,
+        scopes: &[&ast::Node],
+    ) -> Option<BTreeSet<String>> {
+        // the node variables are resolved when building
+        if node
+            .graph
+            .values()
+            .any(|x| x.name == name || x.shortcut.as_deref() == Some(name))
+        {
+            return None;
+        }
+
+        let collect = |node: &ast::Node| {
+            node.graph
+                .values()
+                .flat_map(|x| Some(&x.name).into_iter().chain(&x.shortcut))
+                .cloned()
+                .collect()
+        };
+
+        let child = node
+            .children
+            .get(name)
+            .or_else(|| scopes.iter().rev().find(|x| x.name == name).copied());
+        match child {
+            Some(child) => Some(collect(child)),
+            None => self.root.parse_node(name).ok().map(|x| collect(&x.node)),
+        }
+    }
+}
+
+fn collect_node(
+    node: &ast::Node,
+    uses: &mut BTreeSet<String>,
+    shapes: &mut BTreeSet<String>,
+    calls: &mut BTreeSet<String>,
+) {
+    for var in node.graph.values() {
+        if let Some(value) = &var.value {
+            collect_value(value, uses);
+        }
+    }
+    for with in node.withs.values() {
+        for value in with.graph.values() {
+            collect_value(value, uses);
+        }
+    }
+    for graph_node in node.tensor_graph.values() {
+        for call in &graph_node.calls {
+            // the node variables can be called
+            uses.insert(call.name.clone());
+            calls.insert(call.name.clone());
+
+            for value in call.args.iter().flat_map(|x| x.values()) {
+                collect_value(value, uses);
+            }
+            if let Some(repeat) = &call.repeat {
+                collect_value(repeat, uses);
+            }
+        }
+        if let Some(guard) = &graph_node.guard {
+            collect_value(guard, uses);
+        }
+        if let Some(graph_shapes) = &graph_node.shapes {
+            for shape in graph_shapes.0.borrow().values().flatten() {
+                for dim in &shape.0 {
+                    collect_value(dim, uses);
+                    collect_value(dim, shapes);
+                }
+            }
+        }
+    }
+    for child in node.children.values() {
+        collect_node(child, uses, shapes, calls);
+    }
+}
+
+fn collect_value(value: &ast::Value, names: &mut BTreeSet<String>) {
+    match value {
+        ast::Value::Variable(var) => {
+            names.insert(var.borrow().name.clone());
+        }
+        ast::Value::Expr(expr) => {
+            collect_value(&expr.lhs, names);
+            if let Some(rhs) = &expr.rhs {
+                collect_value(rhs, names);
+            }
+        }
+        ast::Value::List(list) => {
+            for value in list {
+                collect_value(value, names);
+            }
+        }
+        ast::Value::Map(map) => {
+            for value in map.values().flatten() {
+                collect_value(value, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_root() -> NodeRoot {
+        NodeRoot::new(Some("../n3-torch/ffi/python/n3"))
+    }
+
+    #[test]
+    fn test_lint_std() {
+        let root = new_root();
+        for name in ["LeNet5", "Vgg", "ImageClassification"].iter() {
+            assert_eq!(root.lint(name).unwrap(), vec![]);
+            root.check(name).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_lint_warnings() {
+        let model = "
+node MyNode:
+    let Ic: input channels = int 32
+    let unused = int 0
+    let W: width = dim
+
+    with Relu:
+        set foo = 1
+
+    with Dropout:
+        set P = 0.3
+        set probability = 0.3
+        set Q = 1
+
+    node Linear:
+        1. Relu
+        2. Dropout
+
+    0. Input    = Ic
+    2. Linear   = 10
+";
+        let root = new_root();
+
+        let node = "MyNode".to_string();
+        let parser = crate::Parser::default();
+        let file = parser.parse_file(model).unwrap();
+
+        let mut lint = Lint {
+            root: &root,
+            names: root.node_names(),
+            warnings: vec![],
+        };
+        lint.lint_node(&file.node, &[], &[]);
+
+        assert_eq!(
+            lint.warnings,
+            vec![
+                LintWarning::UnusedVariable {
+                    node: node.clone(),
+                    name: "unused".to_string(),
+                },
+                LintWarning::UnusedVariable {
+                    node: node.clone(),
+                    name: "width".to_string(),
+                },
+                LintWarning::UnknownWithKey {
+                    node: node.clone(),
+                    with: "Dropout".to_string(),
+                    name: "Q".to_string(),
+                },
+                LintWarning::UnknownWithKey {
+                    node: node.clone(),
+                    with: "Relu".to_string(),
+                    name: "foo".to_string(),
+                },
+                LintWarning::MismatchedId {
+                    node: node.clone(),
+                    expected: 1,
+                    given: 2,
+                },
+                LintWarning::ShadowedNode {
+                    node,
+                    name: "Linear".to_string(),
+                },
+            ]
+        );
+    }
+}
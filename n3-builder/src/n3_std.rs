@@ -4,11 +4,11 @@ use std::path::Path;
 use glob::glob;
 use inflector::Inflector;
 
-pub fn get_sources(root: &Path) -> HashMap<String, String> {
+pub fn get_source_paths(root: &Path) -> HashMap<String, String> {
     get_files(root, "n3")
 }
 
-pub fn get_externs(root: &Path) -> HashMap<String, String> {
+pub fn get_extern_paths(root: &Path) -> HashMap<String, String> {
     get_files(root, "py")
 }
 
@@ -16,7 +16,7 @@ fn get_files(root: &Path, extension: &'static str) -> HashMap<String, String> {
     let result: HashMap<_, _> = glob(&format!("{}/std/**/*.{}", root.display(), extension))
         .unwrap()
         .filter_map(|e| e.ok())
-        .map(|p| (trim_path(&p), load_source(&p)))
+        .map(|p| (trim_path(&p), p.display().to_string()))
         .collect();
 
     if result.is_empty() {
@@ -31,7 +31,3 @@ pub fn trim_path(path: &Path) -> String {
     let filename = filename.replace("_", "-").to_pascal_case();
     filename.replace("-", "")
 }
-
-fn load_source(path: &Path) -> String {
-    std::fs::read_to_string(path).unwrap()
-}
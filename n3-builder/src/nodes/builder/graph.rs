@@ -1,8 +1,9 @@
 use super::node::{ExternTensorGraphCondition, NodeEntry};
 use crate::ast;
-use crate::error::{GraphCallError, Result};
+use crate::error::{GraphCallError, GraphNodeError, LinkError, Result};
 use crate::externs::{ExternIR, ExternIRShapes};
 use crate::graph::Graph;
+use crate::nodes::ir::build_guard;
 use crate::tensor::IRData;
 use crate::variable::{assert_equal, BuildValue, Link};
 
@@ -64,16 +65,27 @@ impl<'a, 'b, 'c> GraphNodeBuilder<DefaultNode> for GraphNodeEntry<'a, 'b, 'c> {
         let root = self.root;
         let id = self.node.id;
 
+        let mut shared_nodes = vec![];
+
         for call in self.node.calls.into_iter() {
             // Step 1. get the node
             let mut callee = root.get(&call.name)?;
+
+            // Step 1-1. substitute the node variables
+            let mut args = call.args.unwrap_or_default();
+            if !args.is_empty() {
+                callee = root.get_generic(callee, &mut args)?;
+            }
+
             let graph = root.graph.borrow();
 
             callee.set_id(id);
             callee.set_repeat(graph.replace_to(call.repeat)?);
+            // note: the guard is already replaced
+            callee.set_guard(self.node.guard.clone());
 
             // Step 2. apply variables
-            if let Some(args) = call.args {
+            if !args.is_empty() {
                 let args = args
                     .into_iter()
                     .map(|(k, v)| {
@@ -134,16 +146,64 @@ impl<'a, 'b, 'c> GraphNodeBuilder<DefaultNode> for GraphNodeEntry<'a, 'b, 'c> {
                 }
             }
 
-            // Step 5. store
+            // Step 5. share the parameters
+            if let Some(shared) = call.shared {
+                match root.shared.get(&shared) {
+                    Some(&index) => {
+                        let origin = &root.tensor_graph[index];
+                        if origin.name() != callee.name() {
+                            return GraphCallError::MismatchedSharedNode {
+                                shared,
+                                expected: origin.name().to_string(),
+                                given: callee.name().to_string(),
+                            }
+                            .into();
+                        }
+                        shared_nodes.push((index, root.tensor_graph.len()));
+                    }
+                    None => {
+                        root.shared.insert(shared.clone(), root.tensor_graph.len());
+                    }
+                }
+                callee.set_shared(Some(shared));
+            }
+
+            // Step 6. store
             root.tensor_graph.push(callee);
         }
 
-        // Step 6. merge dedicated shapes
+        // Step 7. merge dedicated shapes
         if let Some(shapes) = self.node.shapes {
             if let Some(last_outputs) = root.get_output_shapes() {
                 shapes.link_to(last_outputs)?;
             }
         }
+
+        // Step 8. test the shapes of the shared nodes
+        for (origin, given) in shared_nodes {
+            let origin = &root.tensor_graph[origin];
+            let given = &root.tensor_graph[given];
+
+            assert_shared_shapes(origin.get_input_shapes(), given.get_input_shapes())?;
+            assert_shared_shapes(origin.get_output_shapes(), given.get_output_shapes())?;
+        }
+        Ok(())
+    }
+}
+
+struct SkippedNode;
+impl<'a, 'b, 'c> GraphNodeBuilder<SkippedNode> for GraphNodeEntry<'a, 'b, 'c> {
+    fn build(self) -> Result<()> {
+        let root = self.root;
+
+        // identity
+        if let Some(shapes) = self.node.shapes {
+            if let Some(last_outputs) = root.get_output_shapes() {
+                shapes.link_to(last_outputs)?;
+            }
+        }
+
+        root.skipped.insert(self.node.id);
         Ok(())
     }
 }
@@ -193,6 +253,7 @@ fn build_extern(
             graph: graph.into(),
             input: io_input,
             output: io_output,
+            shared: None,
         },
         shapes: ExternIRShapes {
             input: Some(input),
@@ -459,16 +520,44 @@ impl<'a, 'b, 'c> GraphNodeEntry<'a, 'b, 'c> {
         self.node.id == 0
     }
 
-    pub fn build(self) -> Result<()> {
+    /// Tests the guard, or returns `None` if it is deferred until the node is built.
+    fn test_guard(&mut self) -> Result<Option<bool>> {
+        let guard = match self.node.guard.take() {
+            Some(guard) => guard,
+            None => return Ok(Some(true)),
+        };
+
+        let guard = self.root.graph.borrow().replace_to(Some(guard))?.unwrap();
+        let value = build_guard(&guard)?;
+        if value.is_none() {
+            self.node.guard = Some(guard);
+        }
+        Ok(value)
+    }
+
+    fn is_builtin(&self) -> bool {
+        [NODE__Transform, NODE__ToLinear, NODE__Concat].contains(&self.node.calls[0].name.as_str())
+    }
+
+    pub fn build(mut self) -> Result<()> {
         if self.is_input() {
             // input node
             GraphNodeBuilder::<InputNode>::build(self)
         } else {
-            match_builtins!(self =>
-                Transform,
-                ToLinear,
-                Concat,
-            )
+            match self.test_guard()? {
+                // dropped node
+                Some(false) => GraphNodeBuilder::<SkippedNode>::build(self),
+                // the built-in nodes cannot defer the guards
+                None if self.is_builtin() => GraphNodeError::UnresolvedGuard {
+                    value: self.node.guard.unwrap().build(),
+                }
+                .into(),
+                _ => match_builtins!(self =>
+                    Transform,
+                    ToLinear,
+                    Concat,
+                ),
+            }
         }
     }
 }
@@ -503,6 +592,43 @@ fn unwrap_dict(inputs: ast::GraphInputs) -> Result<ast::Outs> {
     })
 }
 
+fn assert_shared_shapes(origin: Option<&ast::Shapes>, given: Option<&ast::Shapes>) -> Result<()> {
+    let (origin, given) = match origin.zip(given) {
+        Some((origin, given)) => (origin.0.borrow(), given.0.borrow()),
+        None => return Ok(()),
+    };
+
+    if origin.len() != given.len() || origin.keys().any(|x| !given.contains_key(x)) {
+        return GraphCallError::MismatchedShapeKeys {
+            expected: origin.keys().cloned().collect(),
+            given: given.keys().cloned().collect(),
+        }
+        .into();
+    }
+
+    for (name, origin) in origin.iter() {
+        if let Some((origin, given)) = origin.as_ref().zip(given[name].as_ref()) {
+            if origin.0.len() != given.0.len() {
+                return LinkError::MismatchedShape {
+                    expected: origin.clone(),
+                    given: given.clone(),
+                }
+                .into();
+            }
+
+            // note: only the determined dims can be compared
+            for (origin, given) in origin.0.iter().zip(given.0.iter()) {
+                let origin = origin.build();
+                let given = given.build();
+                if origin.is_atomic() && given.is_atomic() {
+                    assert_equal(origin, given)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn unwrap_value<T>(name: &str, value: Option<T>) -> Result<T> {
     value.ok_or_else(|| {
         GraphCallError::GenericShape {
@@ -1,10 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
 
-use super::super::ir::NodeIR;
+use super::super::ir::{NodeIR, NodeTemplate};
 use super::graph::GraphNodeEntry;
 use crate::ast;
 use crate::context::{Context, NodeName};
-use crate::error::{ExecBuildError, GraphCallError, GraphNodeError, Result};
+use crate::error::{
+    ExecBuildError, GraphCallError, GraphError, GraphNodeError, PackageError, Result,
+};
 use crate::execs::ExecIR;
 use crate::externs::ExternIR;
 use crate::graph::{Graph, RefGraph};
@@ -27,6 +30,11 @@ where
 
     pub tensor_graph: TensorGraph,
     pub last_tensor_id: u64,
+
+    // the indices of the shared nodes in the tensor graph
+    pub shared: BTreeMap<String, usize>,
+    // the ids of the graph nodes dropped by the guards
+    pub skipped: BTreeSet<u64>,
 }
 
 impl<'a, 'b> NodeEntry<'a, 'b> {
@@ -37,6 +45,8 @@ impl<'a, 'b> NodeEntry<'a, 'b> {
             ctx,
             tensor_graph: Default::default(),
             last_tensor_id: 0,
+            shared: Default::default(),
+            skipped: Default::default(),
         }
     }
 
@@ -56,10 +66,16 @@ impl<'a, 'b> NodeEntry<'a, 'b> {
     }
 
     fn add_use(&mut self, name: String, u: ast::Use) -> Result<()> {
-        // Step 1. get the source
-        // Step 2. build
-        // Step 3. store
-        todo!()
+        // Step 1. get the source & build
+        let node = match u.by {
+            ast::UseBy::Local => self.ctx.root.get_any(&u.name)?,
+            ast::UseBy::Repo { author } => self.ctx.root.get_by_author(&u.name, &author)?,
+            ast::UseBy::Web { source } => return PackageError::UnsupportedSource { source }.into(),
+        };
+
+        // Step 2. store
+        self.ctx.add_use(name, node);
+        Ok(())
     }
 
     fn add_with(&mut self, name: String, with: ast::With) -> Result<()> {
@@ -117,7 +133,7 @@ impl<'a, 'b> NodeEntry<'a, 'b> {
         }
     }
 
-    fn build(mut self) -> NodeIR {
+    fn build(mut self, template: Option<NodeTemplate>) -> NodeIR {
         NodeIR {
             data: IRData::with_tensor_graph(
                 self.name.pop().unwrap(),
@@ -127,11 +143,121 @@ impl<'a, 'b> NodeEntry<'a, 'b> {
             ty: ast::LetNodeType::Default,
             tensor_graph: self.tensor_graph,
             repeat: None,
+            guard: None,
+            template: template.map(Rc::new),
         }
     }
 
     pub fn get(&mut self, name: &str) -> Result<TensorNode> {
-        self.ctx.get(&self.name, name)
+        match self.get_node_variable(name)? {
+            // generic node
+            Some((node_name, ty)) => {
+                let node = self.ctx.get(&self.name, &node_name)?.unwrap_node()?;
+                if !node.is_compatible_with(ty) {
+                    return GraphCallError::MismatchedNodeType {
+                        name: name.to_string(),
+                        expected: ty,
+                        given: node.ty,
+                    }
+                    .into();
+                }
+                Ok(node.into())
+            }
+            None => self.ctx.get(&self.name, name),
+        }
+    }
+
+    fn get_node_variable(&self, name: &str) -> Result<Option<(String, ast::LetNodeType)>> {
+        let graph = self.graph.borrow();
+        let var = match graph.get_node_variable(name) {
+            Some(var) => var.borrow(),
+            None => return Ok(None),
+        };
+
+        let ty = match var.ty {
+            Some(ast::LetType::Node(Some(ty))) => ty,
+            _ => {
+                return GraphError::MismatchedType {
+                    name: var.name.clone(),
+                    expected: ast::LetType::Node(Some(ast::LetNodeType::Default)),
+                    given: var.ty.clone(),
+                }
+                .into()
+            }
+        };
+        match var.value.as_ref().and_then(|x| x.unwrap_node_name()) {
+            Some(node_name) => Ok(Some((node_name.to_string(), ty))),
+            None => GraphError::EmptyValue {
+                name: var.name.clone(),
+                expected: ast::LetType::Node(Some(ty)),
+            }
+            .into(),
+        }
+    }
+
+    /// Rebuilds the generic node, substituting its node variables with the given args.
+    pub fn get_generic(&mut self, node: TensorNode, args: &mut ast::Keywords) -> Result<TensorNode> {
+        let template = match &node {
+            TensorNode::Node(NodeIR {
+                template: Some(template),
+                ..
+            }) => template.clone(),
+            _ => return Ok(node),
+        };
+
+        // Step 1. collect the node variables
+        let names: Vec<_> = {
+            let graph = node.get_graph().borrow();
+            args.keys()
+                .filter(|&k| graph.get_node_variable(k).is_some())
+                .cloned()
+                .collect()
+        };
+        if names.is_empty() {
+            return Ok(node);
+        }
+
+        let nodes = names
+            .into_iter()
+            .map(|name| {
+                let value = args.remove(&name).unwrap();
+                Ok((name, self.get_node_name(value)?))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        // Step 2. substitute the node variables
+        let mut file = template.parse(self.ctx.root, node.name())?;
+        for var in file.node.graph.values_mut() {
+            let name = var.shortcut.as_ref().unwrap_or(&var.name);
+            if let Some(node_name) = nodes.get(name) {
+                var.value = Some(ast::Value::Node(node_name.clone()));
+            }
+        }
+
+        // Step 3. rebuild, with the source of the template
+        let source = std::mem::replace(&mut self.ctx.source, template.source.clone());
+        let node = file.build(self.ctx, template.parent.clone());
+        self.ctx.source = source;
+        node
+    }
+
+    fn get_node_name(&self, value: ast::Value) -> Result<String> {
+        match value {
+            ast::Value::Node(name) => Ok(name),
+            ast::Value::Variable(var) => {
+                let name = var.borrow().name.clone();
+                // pass through the node variable
+                match self.get_node_variable(&name)? {
+                    Some((node_name, _)) => Ok(node_name),
+                    None => Ok(name),
+                }
+            }
+            value => GraphCallError::MismatchedArgType {
+                expected: ast::LetType::Node(None),
+                given: if value.is_atomic() { value.ty() } else { None },
+            }
+            .into(),
+        }
     }
 
     pub fn get_output_shapes(&self) -> Option<&ast::Shapes> {
@@ -144,10 +270,16 @@ impl<'a, 'b> NodeEntry<'a, 'b> {
     }
 
     pub fn fetch_shape(&self, out: &mut ast::Out) -> Result<Option<ast::Shape>> {
+        // the skipped nodes pass through the last outputs
+        let mut target = out.id;
+        while let Some(id) = target.filter(|id| self.skipped.contains(id)) {
+            target = Some(id - 1);
+        }
+
         for node in self.tensor_graph.iter().rev() {
             // test id
             let node_id = node.get_id();
-            if let Some(id) = &out.id {
+            if let Some(id) = &target {
                 if node_id > *id {
                     continue;
                 }
@@ -179,6 +311,16 @@ impl<'a> ASTBuild<'a> for ast::File {
             return Ok(ExecFile(self).build(ctx, ())?.into());
         }
 
+        // generic node
+        let template = if self.node.graph.values().any(|x| x.ty.is_node()) {
+            Some(NodeTemplate {
+                parent: parent.clone(),
+                source: ctx.source.clone(),
+            })
+        } else {
+            None
+        };
+
         let mut node = self.node;
 
         let mut name = parent;
@@ -186,7 +328,7 @@ impl<'a> ASTBuild<'a> for ast::File {
 
         // Step 1. make a graph
         let graph: RefGraph =
-            Graph::try_with_variables(ctx.root.seed.generate(), node.graph, false)?.into();
+            Graph::try_with_variables(ctx.root.seed.generate(), node.graph, true)?.into();
         ctx.add_graph(name.clone(), graph.clone());
 
         let mut entry = NodeEntry::new(name, graph, ctx);
@@ -215,7 +357,7 @@ impl<'a> ASTBuild<'a> for ast::File {
         }
 
         // Step 7. store
-        Ok(entry.build().into())
+        Ok(entry.build(template).into())
     }
 }
 
@@ -293,6 +435,8 @@ impl<'a, 'b> ExternNodeEntry<'a, 'b> {
             ty: ast::LetNodeType::Extern(self.ty),
             tensor_graph,
             repeat: None,
+            guard: None,
+            template: None,
         }
     }
 }
@@ -412,6 +556,16 @@ impl<'a> ExternTensorGraphCondition<'a> {
             }
         }
 
+        // Step 5-1. test sharing (the built-in nodes have no parameters to share)
+        if call.shared.is_some() {
+            return GraphCallError::UnexpectedShared.into();
+        }
+
+        // Step 5-2. test the guard
+        if node.guard.is_some() {
+            return GraphNodeError::UnexpectedGuard.into();
+        }
+
         // Step 6. test the args
         if let Some(expected) = self.args {
             #[cfg(feature = "test-nightly")]
@@ -482,6 +636,11 @@ impl ExecNodeEntry {
                     return GraphNodeError::UnexpectedShapes.into();
                 }
 
+                // test the guard
+                if node.guard.is_some() {
+                    return GraphNodeError::UnexpectedGuard.into();
+                }
+
                 // test the calls
                 if node.calls.is_empty() {
                     return GraphNodeError::EmptyCalls.into();
@@ -498,6 +657,9 @@ impl ExecNodeEntry {
                         if call.repeat.is_some() {
                             return GraphCallError::UnexpectedRepeat.into();
                         }
+                        if call.shared.is_some() {
+                            return GraphCallError::UnexpectedShared.into();
+                        }
                         Ok(call.name)
                     })
                     .collect()
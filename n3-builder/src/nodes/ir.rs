@@ -1,9 +1,11 @@
+use std::rc::Rc;
+
 use super::code::NodeCode;
 use super::root::NodeRoot;
 use crate::ast;
-use crate::code::{Code, CodeData, DataFromIR};
-use crate::context::{Build, CloneSafe};
-use crate::error::{GraphCallError, Result};
+use crate::code::{Code, CodeData, CodeShapes, DataFromIR, ShapesFromIR};
+use crate::context::{Build, CloneSafe, NodeName};
+use crate::error::{GraphCallError, GraphNodeError, Result};
 use crate::graph::Graph;
 use crate::seed::Seed;
 use crate::tensor::{IRData, TensorGraph, TensorNode};
@@ -15,9 +17,52 @@ pub struct NodeIR {
     pub ty: ast::LetNodeType,
     pub tensor_graph: TensorGraph,
     pub repeat: Option<ast::Value>,
+    // the guard which depends on the unresolved variables
+    pub guard: Option<ast::Value>,
+    pub template: Option<Rc<NodeTemplate>>,
+}
+
+/// The source of a generic node, which is rebuilt when its node variables are substituted.
+#[derive(Debug, PartialEq)]
+pub struct NodeTemplate {
+    pub parent: NodeName,
+    // the source of the file which defines the node
+    pub source: Rc<String>,
+}
+
+impl NodeTemplate {
+    /// Parses the node from the source, following the parents.
+    pub fn parse(&self, root: &NodeRoot, name: &str) -> Result<ast::File> {
+        let file = root.parser.parse_file(&self.source)?;
+        if self.parent.is_empty() {
+            return Ok(file);
+        }
+
+        // note: the children don't have their own uses
+        let mut node = file.node;
+        for child in self.parent[1..]
+            .iter()
+            .map(|x| x.as_str())
+            .chain(Some(name))
+        {
+            // the source always has the children which built the template
+            node = node.children.remove(child).unwrap();
+        }
+        Ok(ast::File {
+            uses: Default::default(),
+            node,
+        })
+    }
 }
 
 impl NodeIR {
+    pub fn is_compatible_with(&self, ty: ast::LetNodeType) -> bool {
+        // the normal extern node can be applied into normal node.
+        self.ty == ty
+            || self.ty == ast::LetNodeType::Extern(ast::ExternNodeType::Default)
+                && ty == ast::LetNodeType::Default
+    }
+
     pub fn get_input_shapes(&self) -> Option<&ast::Shapes> {
         self.tensor_graph.get_input_shapes()
     }
@@ -26,6 +71,25 @@ impl NodeIR {
         self.tensor_graph.get_output_shapes()
     }
 
+    /// Returns the input and output shapes, resolved with the current variables.
+    pub fn get_shapes(&self) -> CodeShapes {
+        CodeShapes::from_ir(self.get_input_shapes(), self.get_output_shapes())
+    }
+
+    /// Tests the deferred guard with the current variables.
+    pub fn test_guard(&self) -> Result<bool> {
+        match &self.guard {
+            Some(guard) => match build_guard(guard)? {
+                Some(value) => Ok(value),
+                None => GraphNodeError::UnresolvedGuard {
+                    value: guard.build(),
+                }
+                .into(),
+            },
+            None => Ok(true),
+        }
+    }
+
     pub fn build(mut self, root: &NodeRoot) -> Result<Code> {
         if let Some(repeat) = &self.repeat {
             let repeat = repeat.build();
@@ -110,6 +174,7 @@ impl NodeIR {
                 // pass the IO Outs
                 node.data.input = self.data.input;
                 node.data.output = self.data.output;
+                node.data.shared = self.data.shared;
 
                 return Ok(node.build()?.into());
             } else {
@@ -118,6 +183,9 @@ impl NodeIR {
             }
         }
 
+        // the node variables are already substituted
+        self.data.graph.borrow_mut().prune_nodes();
+
         let tensor_graph = self.tensor_graph.build(root)?;
 
         Ok(NodeCode {
@@ -128,6 +196,19 @@ impl NodeIR {
     }
 }
 
+/// Resolves the guard into a bool, or returns `None` if it depends on the unresolved variables.
+pub fn build_guard(guard: &ast::Value) -> Result<Option<bool>> {
+    match guard.build() {
+        ast::Value::Bool(value) => Ok(Some(value)),
+        value if value.is_atomic() => GraphCallError::MismatchedArgType {
+            expected: ast::LetType::Bool,
+            given: value.ty(),
+        }
+        .into(),
+        _ => Ok(None),
+    }
+}
+
 impl Build for NodeIR {
     type Output = TensorNode;
 
@@ -140,7 +221,7 @@ impl CloneSafe for NodeIR {
     fn clone_safe(&self, seed: &Seed, variables: &mut Vec<ast::RefVariable>) -> Self {
         match self.ty {
             // extern node wrapper
-            // note: ordered (extern_node -> graph(copy) -> data -> repeat)
+            // note: ordered (extern_node -> graph(copy) -> data -> repeat -> guard)
             ast::LetNodeType::Extern(_) => {
                 let tensor_graph = self.tensor_graph.clone_safe(seed, variables);
                 let node = tensor_graph.try_borrow_extern_node().unwrap();
@@ -154,14 +235,18 @@ impl CloneSafe for NodeIR {
                     ty: self.ty,
                     tensor_graph,
                     repeat: self.repeat.clone_value(variables),
+                    guard: self.guard.clone_value(variables),
+                    template: self.template.clone(),
                 }
             }
-            // note: ordered (data -> tensor_graph -> repeat)
+            // note: ordered (data -> tensor_graph -> repeat -> guard)
             ast::LetNodeType::Default => Self {
                 data: self.data.clone_safe(seed, variables),
                 ty: self.ty,
                 tensor_graph: self.tensor_graph.clone_safe(seed, variables),
                 repeat: self.repeat.clone_value(variables),
+                guard: self.guard.clone_value(variables),
+                template: self.template.clone(),
             },
         }
     }
@@ -6,20 +6,21 @@ mod root;
 pub use self::builder::{builtins, ASTBuild};
 pub use self::code::NodeCode;
 pub use self::ir::NodeIR;
-pub use self::root::NodeRoot;
+pub use self::root::{NodeRoot, SharedNodeRoot};
 
 #[cfg(test)]
 mod tests {
     use std::cell::RefCell;
     use std::collections::BTreeMap;
     use std::fs;
+    use std::path::Path;
     use std::rc::Rc;
 
     use maplit::btreemap;
 
     use super::super::*;
     use super::*;
-    use crate::error::TensorNodeError;
+    use crate::error::{BuildError, Error, GraphCallError, GraphNodeError, TensorNodeError};
     use crate::externs::ExternIR;
     use crate::graph::Graph;
     use crate::tensor::IRData;
@@ -46,6 +47,7 @@ mod tests {
                             shortcut: None,
                             ty,
                             value: Some(v),
+                            constraint: None,
                         },
                     )
                 })
@@ -62,6 +64,7 @@ mod tests {
                 output: btreemap! {
                     "x".to_string() => ast::Out::new(ox, "x".to_string()),
                 },
+                shared: None,
             };
             ExternIR {
                 ty: ast::ExternNodeType::Default,
@@ -79,6 +82,8 @@ mod tests {
             ty: ast::LetNodeType::Default,
             tensor_graph,
             repeat: None,
+            guard: None,
+            template: None,
         };
         let root = new_root();
         ir.build(&root).unwrap();
@@ -87,17 +92,31 @@ mod tests {
     #[test]
     fn test_unexpected_extern_node() {
         let root = new_root();
+        let candidates = crate::n3_std::get_extern_paths(Path::new("../n3-torch/ffi/python/n3"))
+            .into_keys()
+            .collect();
         assert_eq!(
             root.get_extern("FOO").err(),
             Some(
                 TensorNodeError::NoSuchNode {
                     name: "FOO".to_string(),
+                    candidates,
                 }
                 .into()
             )
         );
     }
 
+    #[test]
+    fn test_suggest_node() {
+        let root = new_root();
+        let error = root.get("Linaer").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "build error: no such node `Linaer`; did you mean `Linear`?"
+        );
+    }
+
     #[test]
     fn test_build_process() {
         let model = "
@@ -190,6 +209,223 @@ node MyNode:
         ir.build(&root).unwrap();
     }
 
+    #[test]
+    fn test_build_shared() {
+        let model = "
+node MyNode:
+    0. Input                    = 16
+    1. Linear @shared(enc)      = 32
+    2. Relu
+    3. Linear
+    4. Linear @shared(enc)
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+        let ir = root.get("MyNode").unwrap();
+        let node = ir.build(&root).unwrap();
+        let node = node.as_node();
+
+        let shared = Some("enc".to_string());
+        assert_eq!(node.tensor_graph[1].data().shared, shared);
+        assert_eq!(node.tensor_graph[3].data().shared, None);
+        assert_eq!(node.tensor_graph[4].data().shared, shared);
+    }
+
+    #[test]
+    fn test_build_shared_mismatched() {
+        let model = "
+node MyNode:
+    0. Input                    = 16
+    1. Linear @shared(enc)      = 32
+    2. Linear @shared(enc)      = 64
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+        assert!(root.get("MyNode").is_err());
+
+        let model = "
+node MyOtherNode:
+    0. Input                    = 16
+    1. Linear @shared(enc)      = 16
+    2. Relu @shared(enc)
+";
+        root.add_source("MyOtherNode".to_string(), model.to_string());
+        assert_eq!(
+            root.get("MyOtherNode").err(),
+            Some(
+                GraphCallError::MismatchedSharedNode {
+                    shared: "enc".to_string(),
+                    expected: "Linear".to_string(),
+                    given: "Relu".to_string(),
+                }
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_guard() {
+        let model = "
+node MyNode:
+    let use_bn = bool yes
+    let use_dropout = bool no
+
+    0. Input                        = 10
+    1. Linear                       = 20
+    2. Linear ? use_dropout         = 20
+    3. Relu ? use_bn
+    4. Linear {x=x$2}               = 30
+    5. Linear ? (use_bn & use_dropout)
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+        let ir = root.get("MyNode").unwrap();
+        let node = ir.build(&root).unwrap();
+        let node = node.as_node();
+
+        let names: Vec<_> = node.tensor_graph.iter().map(|x| &x.data().name).collect();
+        assert_eq!(names, ["AssertShape", "Linear", "Relu", "Linear"]);
+
+        // the dropped nodes pass through the last outputs
+        let node_n4 = node.tensor_graph[3].as_extern();
+        assert_eq!(node_n4.data.input["x"].id, Some(2));
+        assert_eq!(node_n4.data.output["x"].id, Some(5));
+    }
+
+    #[test]
+    fn test_build_guard_unresolved() {
+        let model = "
+node MyNode:
+    let use_bn = bool *
+
+    0. Input                = 10
+    1. Relu ? use_bn
+    2. Linear               = 20
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+
+        // the guard is deferred until the node is built
+        let build = |use_bn: Option<bool>| {
+            let ir = root.get("MyNode").unwrap();
+            if let Some(use_bn) = use_bn {
+                let variables = vec![("use_bn".to_string(), Some(use_bn.into()))];
+                ir.data
+                    .graph
+                    .borrow()
+                    .apply(variables.into_iter().collect(), false)
+                    .unwrap();
+            }
+            ir.build(&root)
+        };
+        assert!(matches!(
+            build(None).err(),
+            Some(Error::BuildError(BuildError::GraphNodeError(
+                GraphNodeError::UnresolvedGuard { .. }
+            )))
+        ));
+
+        let node = build(Some(true)).unwrap();
+        let names: Vec<_> = node
+            .as_node()
+            .tensor_graph
+            .iter()
+            .map(|x| &x.data().name)
+            .collect();
+        assert_eq!(names, ["AssertShape", "Relu", "Linear"]);
+
+        // the dropped nodes pass through the last outputs
+        let node = build(Some(false)).unwrap();
+        let node = node.as_node();
+        let names: Vec<_> = node.tensor_graph.iter().map(|x| &x.data().name).collect();
+        assert_eq!(names, ["AssertShape", "Linear"]);
+        assert_eq!(node.tensor_graph[1].data().input["x"].id, Some(1));
+    }
+
+    #[test]
+    fn test_build_generic() {
+        let model = "
+node MyNode:
+    let Act = node Relu
+
+    node MyBlock:
+        let Act = node Relu
+
+        1. Linear + Act
+
+    0. Input                = 10
+    1. Linear + Act         = 20
+    2. MyBlock              = 30
+    3. MyBlock(Act=Dropout) = 40
+    4. MyGeneric(Act=Dropout) = 50
+";
+        let generic = "
+node MyGeneric:
+    let Act = node Relu
+
+    node MyInner:
+        let Act = node Relu
+
+        1. Linear + Act
+
+    1. MyInner(Act=Act)
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+        root.add_source("MyGeneric".to_string(), generic.to_string());
+        let ir = root.get("MyNode").unwrap();
+        let node = ir.build(&root).unwrap();
+        let node = node.as_node();
+
+        let names = |node: &NodeCode| -> Vec<_> {
+            node.tensor_graph
+                .iter()
+                .map(|x| x.data().name.clone())
+                .collect()
+        };
+        assert_eq!(
+            names(node),
+            [
+                "AssertShape",
+                "Linear",
+                "Relu",
+                "MyBlock",
+                "MyBlock",
+                "MyGeneric"
+            ]
+        );
+        assert_eq!(names(node.tensor_graph[3].as_node()), ["Linear", "Relu"]);
+        assert_eq!(names(node.tensor_graph[4].as_node()), ["Linear", "Dropout"]);
+
+        // the nested templates are parsed from the source of their file
+        let inner = node.tensor_graph[5].as_node().tensor_graph[0].as_node();
+        assert_eq!(names(inner), ["Linear", "Dropout"]);
+    }
+
+    #[test]
+    fn test_build_generic_mismatched() {
+        let model = "
+node MyNode:
+    let Act = data node Relu
+
+    0. Input                = 10
+    1. Linear + Act         = 20
+";
+        let root = new_root();
+        root.add_source("MyNode".to_string(), model.to_string());
+        assert_eq!(
+            root.get("MyNode").err(),
+            Some(
+                GraphCallError::MismatchedNodeType {
+                    name: "Act".to_string(),
+                    expected: ast::LetNodeType::Extern(ast::ExternNodeType::Data),
+                    given: ast::LetNodeType::Extern(ast::ExternNodeType::Default),
+                }
+                .into()
+            )
+        );
+    }
+
     #[test]
     fn test_build_repeat() {
         let model = "
@@ -209,4 +445,72 @@ node MyNode:
         let ir = root.get("MyNode").unwrap();
         ir.build(&root).unwrap();
     }
+
+    #[test]
+    fn test_reload_changed_sources() {
+        let dir = std::env::temp_dir().join("n3-test-reload");
+        fs::create_dir_all(&dir).unwrap();
+        let path_block = dir.join("my_block.n3");
+        let path_node = dir.join("my_node.n3");
+
+        let block_relu = "
+node MyBlock:
+    1. Linear + Relu
+";
+        let block_dropout = "
+node MyBlock:
+    1. Linear + Dropout
+";
+        let model = "
+node MyNode:
+    0. Input    = 10
+    1. MyBlock  = 20
+";
+        fs::write(&path_block, block_relu).unwrap();
+        fs::write(&path_node, model).unwrap();
+
+        let root = new_root();
+        root.add_source_path("MyBlock".to_string(), path_block.display().to_string());
+        root.add_source_path("MyNode".to_string(), path_node.display().to_string());
+
+        let names = |root: &NodeRoot| -> Vec<_> {
+            let node = root.get("MyNode").unwrap().build(root).unwrap();
+            node.as_node().tensor_graph[1]
+                .as_node()
+                .tensor_graph
+                .iter()
+                .map(|x| x.data().name.clone())
+                .collect()
+        };
+        assert_eq!(names(&root), ["Linear", "Relu"]);
+
+        // the dependents should be rebuilt
+        fs::write(&path_block, block_dropout).unwrap();
+        assert_eq!(names(&root), ["Linear", "Dropout"]);
+
+        // the removed nodes should be forgotten
+        fs::remove_file(&path_block).unwrap();
+        root.remove_missing_paths();
+        assert!(root.get("MyNode").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_std() {
+        let dir = std::env::temp_dir().join("n3-test-reload-std");
+        fs::create_dir_all(dir.join("std")).unwrap();
+        fs::write(dir.join("std/my_relu.n3"), "extern node MyRelu:\n").unwrap();
+        fs::write(dir.join("std/my_relu.py"), "").unwrap();
+
+        let root = NodeRoot::new(Some(dir.to_str().unwrap()));
+        assert!(!root.node_names().contains("MyDropout"));
+
+        // the new nodes of the standard library should be found
+        fs::write(dir.join("std/my_dropout.n3"), "extern node MyDropout:\n").unwrap();
+        root.load_std();
+        assert!(root.node_names().contains("MyDropout"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
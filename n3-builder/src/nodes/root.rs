@@ -1,19 +1,58 @@
 use std::cell::UnsafeCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::ir::NodeIR;
-use crate::cache::NodeCache;
-use crate::error::Result;
+use crate::ast;
+use crate::cache::{DiskCache, MemoryCache, NodeCache, NodeSources};
+use crate::context::Build;
+use crate::error::{PackageError, Result, TensorNodeError};
 use crate::execs::{ExecIR, GlobalVars};
 use crate::externs::PythonScript;
 use crate::n3_std;
+use crate::package::{PackageId, PackageIndex};
 use crate::seed::Seed;
 use crate::tensor::TensorNode;
 
+/// The thread-safe sources of a `NodeRoot`, with the compacted nodes built by any of its roots.
+///
+/// Each thread can spawn its own `NodeRoot` from it, which builds the nodes in its own arena.
+#[derive(Clone, Debug)]
+pub struct SharedNodeRoot {
+    sources: Arc<NodeSources>,
+    externs: Arc<NodeSources>,
+    packages: Arc<PackageIndex>,
+    disk_cache: Option<DiskCache>,
+    memory_cache: Arc<MemoryCache>,
+    n3_source_root: PathBuf,
+}
+
+impl SharedNodeRoot {
+    pub fn spawn(&self) -> NodeRoot {
+        NodeRoot {
+            seed: Seed::default(),
+            sources: NodeCache::with_sources(self.sources.clone()),
+            externs: NodeCache::with_sources(self.externs.clone()),
+            packages: self.packages.clone(),
+            disk_cache: self.disk_cache.clone(),
+            memory_cache: self.memory_cache.clone(),
+            n3_source_root: self.n3_source_root.clone(),
+            parser: crate::Parser::default(),
+            _thread_unsafe: UnsafeCell::new(()),
+        }
+    }
+}
+
 pub struct NodeRoot {
     pub(crate) seed: Seed,
     sources: NodeCache<TensorNode>,
     externs: NodeCache<PythonScript>,
+    packages: Arc<PackageIndex>,
+    pub(crate) disk_cache: Option<DiskCache>,
+    pub(crate) memory_cache: Arc<MemoryCache>,
+    n3_source_root: PathBuf,
     pub(crate) parser: crate::Parser,
     _thread_unsafe: UnsafeCell<()>,
 }
@@ -26,8 +65,12 @@ impl NodeRoot {
 
         Self {
             seed: Seed::default(),
-            sources: NodeCache::new(n3_std::get_sources(&n3_source_root)),
-            externs: NodeCache::new(n3_std::get_externs(&n3_source_root)),
+            sources: NodeCache::new(n3_std::get_source_paths(&n3_source_root)),
+            externs: NodeCache::new(n3_std::get_extern_paths(&n3_source_root)),
+            packages: Arc::default(),
+            disk_cache: None,
+            memory_cache: Arc::default(),
+            n3_source_root,
             parser: crate::Parser::default(),
             _thread_unsafe: UnsafeCell::new(()),
         }
@@ -35,6 +78,22 @@ impl NodeRoot {
 }
 
 impl NodeRoot {
+    pub fn share(&self) -> SharedNodeRoot {
+        SharedNodeRoot {
+            sources: self.sources.sources().clone(),
+            externs: self.externs.sources().clone(),
+            packages: self.packages.clone(),
+            disk_cache: self.disk_cache.clone(),
+            memory_cache: self.memory_cache.clone(),
+            n3_source_root: self.n3_source_root.clone(),
+        }
+    }
+
+    /// Stores the built nodes into the given directory, to be reused by the next runs.
+    pub fn set_cache_dir(&mut self, dir: PathBuf) {
+        self.disk_cache = Some(DiskCache::new(dir));
+    }
+
     pub fn add_source(&self, name: String, source: String) {
         self.sources.add_source(name, source);
     }
@@ -47,10 +106,129 @@ impl NodeRoot {
         self.externs.add_path(name, path);
     }
 
+    /// Adds the node defined in a library root.
+    ///
+    /// If the node is defined in several roots, the one with the highest priority is used.
+    pub(crate) fn add_library_path(&self, name: String, path: String, priority: usize) {
+        self.sources.add_path_with_priority(name, path, priority);
+    }
+
+    pub(crate) fn add_library_extern_path(&self, name: String, path: String, priority: usize) {
+        self.externs.add_path_with_priority(name, path, priority);
+    }
+
+    /// Picks up the nodes which have been added to the standard library.
+    pub(crate) fn load_std(&self) {
+        let priority = NodeSources::PRIORITY_STD;
+        for (name, path) in n3_std::get_source_paths(&self.n3_source_root) {
+            self.sources.add_path_with_priority(name, path, priority);
+        }
+        for (name, path) in n3_std::get_extern_paths(&self.n3_source_root) {
+            self.externs.add_path_with_priority(name, path, priority);
+        }
+    }
+
+    pub(crate) fn add_package(&self, id: PackageId, dir: PathBuf) {
+        self.packages.add(id, dir);
+    }
+
+    pub(crate) fn remove_missing_paths(&self) {
+        self.sources.remove_missing_paths();
+        self.externs.remove_missing_paths();
+    }
+
+    pub fn node_names(&self) -> BTreeSet<String> {
+        self.sources.names()
+    }
+
+    /// Returns the file which each node came from.
+    pub fn node_origins(&self) -> BTreeMap<String, String> {
+        self.sources.sources().origins()
+    }
+
+    /// Returns the nodes defined in several roots, with their paths from the used one.
+    pub fn duplicated_nodes(&self) -> BTreeMap<String, Vec<String>> {
+        self.sources.sources().duplicates()
+    }
+
+    /// Parses the node without building it.
+    pub fn parse_node(&self, name: &str) -> Result<ast::File> {
+        let source = self.get_source(name)?;
+        Ok(self.parser.parse_file(&source)?)
+    }
+
+    /// Returns the type of each node, which is parsed without building it.
+    ///
+    /// Note that the nodes which cannot be parsed are skipped.
+    pub fn node_types(&self) -> BTreeMap<String, ast::NodeType> {
+        self.node_names()
+            .into_iter()
+            .filter_map(|name| {
+                let file = self.parse_node(&name).ok()?;
+                Some((name, file.node.ty))
+            })
+            .collect()
+    }
+
+    /// Returns the names of the nodes which can be given to a node variable of the type.
+    ///
+    /// The default node variables accept both the default nodes and the default extern nodes.
+    pub fn node_names_by_type(&self, ty: ast::LetNodeType) -> Vec<String> {
+        self.node_types()
+            .into_iter()
+            .filter(|(_, node_ty)| match (ty, node_ty) {
+                (ast::LetNodeType::Default, ast::NodeType::Default) => true,
+                (ast::LetNodeType::Default, ast::NodeType::Extern(ty)) => {
+                    *ty == ast::ExternNodeType::Default
+                }
+                (ast::LetNodeType::Extern(ty), ast::NodeType::Extern(node_ty)) => ty == *node_ty,
+                _ => false,
+            })
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Returns the names of the exec nodes.
+    pub fn exec_names(&self) -> Vec<String> {
+        self.node_types()
+            .into_iter()
+            .filter(|(_, ty)| ty.is_exec())
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    pub(crate) fn get_source(&self, name: &str) -> Result<String> {
+        match self.sources.get_source(name)? {
+            Some(source) => Ok(source),
+            None => TensorNodeError::NoSuchNode {
+                name: name.to_string(),
+                candidates: self.node_names(),
+            }
+            .into(),
+        }
+    }
+
     pub(crate) fn get(&self, name: &str) -> Result<NodeIR> {
         self.sources.get(name, self)?.unwrap_node()
     }
 
+    pub(crate) fn get_any(&self, name: &str) -> Result<TensorNode> {
+        self.sources.get(name, self)
+    }
+
+    /// Builds the node from the locked packages of the author.
+    pub(crate) fn get_by_author(&self, name: &str, author: &str) -> Result<TensorNode> {
+        match self.packages.find(author, name)? {
+            // note: the nodes of the packages are not cached, as they may shadow the others
+            Some(path) => TensorNode::build(self, name, fs::read_to_string(path)?),
+            None => PackageError::NoSuchUse {
+                name: name.to_string(),
+                author: author.to_string(),
+            }
+            .into(),
+        }
+    }
+
     pub(crate) fn get_exec(&self, name: &str) -> Result<ExecIR> {
         self.sources.get(name, self)?.unwrap_exec()
     }
@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::Path;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::PackageId;
+use crate::error::Result;
+
+/// The exact versions of the packages resolved by the last install (`n3.lock`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub author: String,
+    pub name: String,
+    pub version: Version,
+    // "author/name"
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Ok(toml::from_str(&fs::read_to_string(path)?)?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &PackageId) -> Option<&LockedPackage> {
+        self.packages
+            .iter()
+            .find(|x| x.author == id.author && x.name == id.name)
+    }
+}
+
+impl LockedPackage {
+    pub fn id(&self) -> Result<PackageId> {
+        PackageId::new(&self.author, &self.name)
+    }
+}
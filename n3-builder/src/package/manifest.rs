@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use super::PackageId;
+use crate::error::Result;
+
+/// The manifest of a package, or of the local root (`n3.toml`).
+///
+/// The local root has no `package` section, and only declares its dependencies.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<PackageInfo>,
+    // "author/name" -> version
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, VersionReq>,
+    // python module -> pip requirement (ex. ">=0.8")
+    #[serde(default)]
+    pub python_dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub author: String,
+    pub name: String,
+    pub version: Version,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn dependencies(&self) -> Result<Vec<(PackageId, VersionReq)>> {
+        self.dependencies
+            .iter()
+            .map(|(id, req)| Ok((id.parse()?, req.clone())))
+            .collect()
+    }
+}
+
+impl PackageInfo {
+    pub fn id(&self) -> Result<PackageId> {
+        PackageId::new(&self.author, &self.name)
+    }
+}
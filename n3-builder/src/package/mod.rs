@@ -0,0 +1,453 @@
+mod lock;
+mod manifest;
+mod registry;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use glob::glob;
+pub use semver::{Version, VersionReq};
+
+use crate::error::{Error, PackageError, Result};
+use crate::n3_std::trim_path;
+
+pub use self::lock::{LockedPackage, Lockfile};
+pub use self::manifest::{Manifest, PackageInfo};
+pub use self::registry::Registry;
+
+pub const MANIFEST_FILE: &str = "n3.toml";
+pub const LOCK_FILE: &str = "n3.lock";
+
+/// The name of a package, written as `author/name`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageId {
+    pub author: String,
+    pub name: String,
+}
+
+impl FromStr for PackageId {
+    type Err = Error;
+
+    fn from_str(id: &str) -> Result<Self> {
+        let mut tokens = id.split('/');
+        match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(author), Some(name), None) => Self::new(author, name),
+            _ => PackageError::InvalidId { id: id.to_string() }.into(),
+        }
+    }
+}
+
+impl PackageId {
+    /// Validates the names, as they are joined to the paths of the packages.
+    ///
+    /// Only the letters, digits, `_` and `-` are accepted, so `.`, `..` and the separators are not.
+    pub fn new(author: &str, name: &str) -> Result<Self> {
+        let is_valid = |x: &str| {
+            !x.is_empty()
+                && x.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        };
+        if is_valid(author) && is_valid(name) {
+            Ok(Self {
+                author: author.to_string(),
+                name: name.to_string(),
+            })
+        } else {
+            PackageError::InvalidId {
+                id: format!("{}/{}", author, name),
+            }
+            .into()
+        }
+    }
+}
+
+impl fmt::Display for PackageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", &self.author, &self.name)
+    }
+}
+
+/// The packages installed in a directory, laid out as `{author}/{name}`.
+#[derive(Clone, Debug)]
+pub struct Packages {
+    dir: PathBuf,
+}
+
+impl Packages {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn path(&self, id: &PackageId) -> PathBuf {
+        self.dir.join(&id.author).join(&id.name)
+    }
+
+    /// Returns the version of the package, if installed.
+    pub fn get(&self, id: &PackageId) -> Option<PackageInfo> {
+        let path = self.path(id).join(MANIFEST_FILE);
+        Manifest::load(&path).ok()?.package
+    }
+
+    pub fn installed(&self) -> Result<Vec<Manifest>> {
+        let path = self.dir.join("*/*").join(MANIFEST_FILE);
+        let path = path.display().to_string();
+
+        let mut manifests = vec![];
+        for path in glob(&path)? {
+            let manifest = Manifest::load(&path?)?;
+            if manifest.package.is_some() {
+                manifests.push(manifest);
+            }
+        }
+        Ok(manifests)
+    }
+
+    /// Installs the dependencies of the manifest transitively, and returns the new lockfile.
+    ///
+    /// The locked versions are preferred while they match the requirements.
+    pub fn install(
+        &self,
+        registry: &Registry,
+        manifest: &Manifest,
+        lock: &Lockfile,
+    ) -> Result<Lockfile> {
+        let mut resolved: BTreeMap<PackageId, LockedPackage> = BTreeMap::new();
+
+        // Step 1. resolve & install the dependencies
+        let mut queue: VecDeque<_> = manifest.dependencies()?.into();
+        while let Some((id, req)) = queue.pop_front() {
+            if let Some(locked) = resolved.get(&id) {
+                if req.matches(&locked.version) {
+                    continue;
+                }
+                return PackageError::ConflictedVersions {
+                    id: id.to_string(),
+                    version: locked.version.clone(),
+                    req,
+                }
+                .into();
+            }
+
+            let version = match lock.get(&id) {
+                Some(locked) if req.matches(&locked.version) => locked.version.clone(),
+                _ => registry.resolve(&id, &req)?,
+            };
+
+            // note: the same version is not installed again
+            let path = self.path(&id);
+            if self.get(&id).map(|x| x.version) != Some(version.clone()) {
+                if path.exists() {
+                    self.remove(&id)?;
+                }
+                copy_dir(&registry.path(&id, &version), &path)?;
+            }
+
+            let dependencies = Manifest::load(&path.join(MANIFEST_FILE))?.dependencies()?;
+            resolved.insert(
+                id.clone(),
+                LockedPackage {
+                    author: id.author,
+                    name: id.name,
+                    version,
+                    dependencies: dependencies.iter().map(|(x, _)| x.to_string()).collect(),
+                },
+            );
+            queue.extend(dependencies);
+        }
+
+        // Step 2. remove the packages which are not locked anymore
+        for manifest in self.installed()? {
+            let id = manifest.package.unwrap().id()?;
+            if !resolved.contains_key(&id) {
+                self.remove(&id)?;
+                // note: the other packages of the author are kept
+                fs::remove_dir(self.dir.join(&id.author)).ok();
+            }
+        }
+
+        Ok(Lockfile {
+            packages: resolved.into_values().collect(),
+        })
+    }
+
+    /// Removes the installed package, only if it is placed in the directory.
+    fn remove(&self, id: &PackageId) -> Result<()> {
+        let path = self.path(id);
+        let is_inside = match path.strip_prefix(&self.dir) {
+            Ok(path) => path.components().all(|x| matches!(x, Component::Normal(_))),
+            Err(_) => false,
+        };
+        if !is_inside {
+            return PackageError::InvalidId { id: id.to_string() }.into();
+        }
+
+        fs::remove_dir_all(path)?;
+        Ok(())
+    }
+}
+
+/// The locked packages, which resolve the nodes used by the authors (`use X by author`).
+#[derive(Debug, Default)]
+pub struct PackageIndex {
+    packages: RwLock<BTreeMap<PackageId, PathBuf>>,
+}
+
+impl PackageIndex {
+    pub fn add(&self, id: PackageId, dir: PathBuf) {
+        self.packages.write().unwrap().insert(id, dir);
+    }
+
+    /// Returns the path of the node, from the packages of the author.
+    ///
+    /// Note that the node should be defined in only one of them.
+    pub fn find(&self, author: &str, name: &str) -> Result<Option<PathBuf>> {
+        let packages = self.packages.read().unwrap();
+
+        let mut found = vec![];
+        for (id, dir) in packages.iter().filter(|(id, _)| id.author == author) {
+            let path = dir.join("**/*.n3").display().to_string();
+            for path in glob(&path)? {
+                let path = path?;
+                if trim_path(&path) == name {
+                    found.push((id, path));
+                }
+            }
+        }
+
+        if found.len() > 1 {
+            return PackageError::AmbiguousUse {
+                name: name.to_string(),
+                author: author.to_string(),
+                packages: found.iter().map(|(id, _)| id.to_string()).collect(),
+            }
+            .into();
+        }
+        Ok(found.pop().map(|(_, path)| path))
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &path)?;
+        } else {
+            fs::copy(entry.path(), path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Deref;
+
+    use super::*;
+    use crate::execs::{dirs, ExecRoot, ExecRootConfig, GlobalVars};
+
+    fn publish(registry: &Path, id: &str, version: &str, manifest: &str, files: &[(&str, &str)]) {
+        let dir = registry.join(id).join(version);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        for (name, source) in files {
+            fs::write(dir.join(name), source).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_package_id() {
+        let id: PackageId = "kerryeon/my_layers-v2".parse().unwrap();
+        assert_eq!(id.to_string(), "kerryeon/my_layers-v2");
+
+        for id in &[
+            "../nodes",
+            "./x",
+            "..",
+            "kerryeon/..",
+            "kerryeon/",
+            "a/b/c",
+            "a\\b/c",
+        ] {
+            assert!(
+                matches!(
+                    id.parse::<PackageId>(),
+                    Err(Error::PackageError(PackageError::InvalidId { .. })),
+                ),
+                "{}",
+                id
+            );
+        }
+
+        // the packages outside the directory should not be removed
+        let packages = Packages::new(std::env::temp_dir().join("n3-test-package-id"));
+        let id = PackageId {
+            author: "..".to_string(),
+            name: "nodes".to_string(),
+        };
+        assert!(packages.remove(&id).is_err());
+    }
+
+    #[test]
+    fn test_install_packages() {
+        let dir = std::env::temp_dir().join("n3-test-packages");
+        fs::remove_dir_all(&dir).ok();
+        let registry_dir = dir.join("registry");
+        let root_dir = dir.join("root");
+
+        let block = |op: &str| {
+            format!(
+                "
+node MyBlock:
+    1. Linear + {}
+",
+                op
+            )
+        };
+        let model = "
+use MyBlock by kerryeon
+
+node MyModel:
+    0. Input    = 10
+    1. MyBlock  = 20
+";
+        let app = "
+use MyModel by kerryeon
+
+node MyApp:
+    0. Input    = 10
+    1. MyModel  = 20
+";
+
+        publish(
+            &registry_dir,
+            "kerryeon/layers",
+            "0.1.0",
+            "[package]\nauthor = \"kerryeon\"\nname = \"layers\"\nversion = \"0.1.0\"\n",
+            &[("my_block.n3", &block("Relu"))],
+        );
+        publish(
+            &registry_dir,
+            "kerryeon/layers",
+            "0.2.0",
+            "[package]\nauthor = \"kerryeon\"\nname = \"layers\"\nversion = \"0.2.0\"\n",
+            &[("my_block.n3", &block("Dropout"))],
+        );
+        publish(
+            &registry_dir,
+            "kerryeon/models",
+            "1.0.0",
+            "[package]
+author = \"kerryeon\"
+name = \"models\"
+version = \"1.0.0\"
+
+[dependencies]
+\"kerryeon/layers\" = \"^0.1\"
+
+[python-dependencies]
+torchvision = \">=0.8\"
+",
+            &[("my_model.n3", model)],
+        );
+
+        // Step 1. install
+        let registry = Registry::new(registry_dir);
+        let packages = Packages::new(root_dir.join(dirs::PACKAGES_DIR));
+
+        let mut manifest = Manifest::default();
+        manifest
+            .dependencies
+            .insert("kerryeon/models".to_string(), "^1.0".parse().unwrap());
+        let lock = packages
+            .install(&registry, &manifest, &Lockfile::default())
+            .unwrap();
+
+        let versions: Vec<_> = lock
+            .packages
+            .iter()
+            .map(|x| format!("{} {}", x.id().unwrap(), x.version))
+            .collect();
+        assert_eq!(versions, ["kerryeon/layers 0.1.0", "kerryeon/models 1.0.0"]);
+        assert_eq!(packages.installed().unwrap().len(), 2);
+        lock.save(&root_dir.join(LOCK_FILE)).unwrap();
+
+        // Step 2. resolve the used nodes by the author
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, &root_dir.display().to_string())
+            .unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../n3-torch/ffi/python/n3")
+            .unwrap();
+        let config = ExecRootConfig {
+            create_root_dir: Some(true),
+        };
+        let root = ExecRoot::try_new(envs, config).unwrap();
+        root.add_source("MyApp".to_string(), app.to_string());
+
+        // note: the nodes of the packages are not the local nodes
+        assert!(root.deref().get("MyModel").is_err());
+
+        let node = root.deref().get("MyApp").unwrap();
+        let node = node.build(&root).unwrap();
+        let my_model = node.as_node().tensor_graph[1].as_node();
+        let names: Vec<_> = my_model.tensor_graph[1]
+            .as_node()
+            .tensor_graph
+            .iter()
+            .map(|x| x.data().name.clone())
+            .collect();
+        assert_eq!(names, ["Linear", "Relu"]);
+
+        // the same node in several packages of the author should not shadow each other
+        let index = PackageIndex::default();
+        let layers = "kerryeon/layers".parse().unwrap();
+        for (name, version) in &[("layers", "0.1.0"), ("layers-v2", "0.2.0")] {
+            let id = format!("kerryeon/{}", name).parse().unwrap();
+            index.add(id, registry.path(&layers, &version.parse().unwrap()));
+        }
+        assert!(matches!(
+            index.find("kerryeon", "MyBlock"),
+            Err(Error::PackageError(PackageError::AmbiguousUse { .. })),
+        ));
+
+        // Step 3. the conflicted versions should be reported
+        manifest
+            .dependencies
+            .insert("kerryeon/layers".to_string(), "^0.2".parse().unwrap());
+        assert_eq!(
+            packages.install(&registry, &manifest, &lock).err(),
+            Some(
+                PackageError::ConflictedVersions {
+                    id: "kerryeon/layers".to_string(),
+                    version: "0.2.0".parse().unwrap(),
+                    req: "^0.1".parse().unwrap(),
+                }
+                .into()
+            ),
+        );
+
+        // Step 4. the packages which are not locked anymore should be removed
+        let mut manifest = Manifest::default();
+        manifest
+            .dependencies
+            .insert("kerryeon/layers".to_string(), "^0.2".parse().unwrap());
+        let lock = packages.install(&registry, &manifest, &lock).unwrap();
+        assert_eq!(lock.packages.len(), 1);
+        assert_eq!(lock.packages[0].version, "0.2.0".parse().unwrap());
+
+        let installed: Vec<_> = packages
+            .installed()
+            .unwrap()
+            .into_iter()
+            .map(|x| x.package.unwrap().id().unwrap().to_string())
+            .collect();
+        assert_eq!(installed, ["kerryeon/layers"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::PathBuf;
+
+use semver::{Version, VersionReq};
+
+use super::PackageId;
+use crate::error::{PackageError, Result};
+
+/// A directory of the published packages, laid out as `{author}/{name}/{version}`.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    dir: PathBuf,
+}
+
+impl Registry {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn path(&self, id: &PackageId, version: &Version) -> PathBuf {
+        self.dir
+            .join(&id.author)
+            .join(&id.name)
+            .join(version.to_string())
+    }
+
+    /// Returns the published versions of the package, in ascending order.
+    pub fn versions(&self, id: &PackageId) -> Result<Vec<Version>> {
+        let dir = self.dir.join(&id.author).join(&id.name);
+        if !dir.is_dir() {
+            return PackageError::NoSuchPackage { id: id.to_string() }.into();
+        }
+
+        let mut versions = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if let Some(version) = entry
+                .file_name()
+                .to_str()
+                .and_then(|x| Version::parse(x).ok())
+            {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// Returns the latest version which matches the requirement.
+    pub fn resolve(&self, id: &PackageId, req: &VersionReq) -> Result<Version> {
+        match self
+            .versions(id)?
+            .into_iter()
+            .rev()
+            .find(|x| req.matches(x))
+        {
+            Some(version) => Ok(version),
+            None => PackageError::NoMatchingVersion {
+                id: id.to_string(),
+                req: req.clone(),
+            }
+            .into(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// The maximum number of the suggestions to be shown.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Ranks the candidates similar to the given name, the most similar first.
+pub fn suggest<I, S>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let name_norm = normalize(name);
+    let threshold = (name_norm.chars().count() / 3).max(1);
+
+    let mut suggestions: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            if candidate == name {
+                return None;
+            }
+
+            let distance = levenshtein(&name_norm, &normalize(candidate));
+            if distance <= threshold {
+                Some((distance, candidate.to_string()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    suggestions.sort();
+    suggestions.dedup_by(|a, b| a.1 == b.1);
+
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Writes the suggestions as a trailing hint, e.g. "; did you mean `foo`?"
+pub fn fmt_suggestions<I, S>(f: &mut fmt::Formatter<'_>, name: &str, candidates: I) -> fmt::Result
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let suggestions = suggest(name, candidates);
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+
+    let suggestions: Vec<_> = suggestions.iter().map(|x| format!("`{}`", x)).collect();
+    write!(f, "; did you mean {}?", suggestions.join(" or "))
+}
+
+/// Ignores the case and the separators, such as `batch size` and `batch_size`.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+
+    let mut row: Vec<_> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(prev + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let candidates = ["batch_size", "epoch", "learning rate", "LeNet5", "Linear"];
+
+        assert_eq!(suggest("batch size", candidates), ["batch_size"]);
+        assert_eq!(suggest("BatchSize", candidates), ["batch_size"]);
+        assert_eq!(suggest("learning_rat", candidates), ["learning rate"]);
+        assert_eq!(suggest("Linaer", candidates), ["Linear"]);
+        assert_eq!(suggest("LeNet", candidates), ["LeNet5"]);
+        assert!(suggest("foo", candidates).is_empty());
+    }
+}
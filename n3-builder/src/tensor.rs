@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
 use crate::ast;
+use crate::cache::CompactedNode;
 use crate::code::Code;
 use crate::context::{Build, CloneSafe, Context};
 use crate::error::{Result, TensorNodeError};
@@ -9,6 +12,7 @@ use crate::externs::ExternIR;
 use crate::graph::{RefGraph, Values};
 use crate::nodes::{builtins, ASTBuild, NodeIR, NodeRoot};
 use crate::seed::Seed;
+use crate::variable::Link;
 
 #[derive(Default, Debug, PartialEq)]
 pub struct TensorGraph(Vec<TensorNode>);
@@ -41,6 +45,7 @@ pub struct IRData {
     pub graph: RefGraph,
     pub input: ast::Outs,
     pub output: ast::Outs,
+    pub shared: Option<String>,
 }
 
 impl PartialEq for IRData {
@@ -50,6 +55,7 @@ impl PartialEq for IRData {
             && self.graph.eq(&other.graph)
             && self.input.eq(&other.input)
             && self.output.eq(&other.output)
+            && self.shared.eq(&other.shared)
     }
 }
 
@@ -125,7 +131,33 @@ impl TensorGraph {
     }
 
     pub fn build(self, root: &NodeRoot) -> Result<Vec<Code>> {
-        self.0.into_iter().map(|x| x.build_to_code(root)).collect()
+        // the outputs of the dropped nodes -> their inputs
+        let mut dropped: BTreeMap<(Option<u64>, String), ast::Out> = BTreeMap::new();
+
+        let mut codes = vec![];
+        for mut node in self.0 {
+            // pass through the dropped nodes
+            for out in node.get_inputs_mut().values_mut() {
+                if let Some(origin) = dropped.get(&(out.id, out.name.clone())) {
+                    *out = origin.clone();
+                }
+            }
+
+            if node.test_guard()? {
+                codes.push(node.build_to_code(root)?);
+            } else {
+                // the input-output shapes should be same
+                node.get_input_shapes().link_to(&node.get_output_shapes())?;
+
+                let inputs = node.get_inputs();
+                for (name, out) in &node.get_data().output {
+                    if let Some(origin) = inputs.get(name) {
+                        dropped.insert((out.id, name.clone()), origin.clone());
+                    }
+                }
+            }
+        }
+        Ok(codes)
     }
 }
 
@@ -153,6 +185,25 @@ impl TensorNode {
         self.get_data_mut().id = id;
     }
 
+    pub fn set_shared(&mut self, shared: Option<String>) {
+        self.get_data_mut().shared = shared;
+    }
+
+    pub fn set_guard(&mut self, value: Option<ast::Value>) {
+        match self {
+            Self::Node(node) => node.guard = value,
+            _ => unreachable!("Only the default nodes can be guarded."),
+        }
+    }
+
+    /// Tests the guard which is deferred until the node is built.
+    pub fn test_guard(&self) -> Result<bool> {
+        match self {
+            Self::Node(node) => node.test_guard(),
+            _ => Ok(true),
+        }
+    }
+
     pub fn set_repeat(&mut self, value: Option<ast::Value>) {
         match self {
             Self::Node(node) => node.repeat = value,
@@ -289,6 +340,7 @@ impl CloneSafe for IRData {
             graph: self.graph.clone_safe(seed, variables),
             input: self.input.clone(),
             output: self.output.clone(),
+            shared: self.shared.clone(),
         }
     }
 }
@@ -297,6 +349,7 @@ impl Build for TensorNode {
     type Output = Self;
 
     fn build(root: &NodeRoot, name: &str, source: String) -> Result<Self::Output> {
+        let source = Rc::new(source);
         let file = root.parser.parse_file(&source)?;
 
         // test name
@@ -307,10 +360,20 @@ impl Build for TensorNode {
             }
             .into()
         } else {
-            let mut ctx = Context::new(root);
+            let mut ctx = Context::new(root, source);
             file.build(&mut ctx, Default::default())
         }
     }
+
+    fn dump(output: &Self::Output) -> Option<Vec<u8>> {
+        CompactedNode::compact(output).and_then(|x| bincode::serialize(&x).ok())
+    }
+
+    fn load(binary: &[u8]) -> Option<Self::Output> {
+        bincode::deserialize::<CompactedNode>(binary)
+            .ok()
+            .map(CompactedNode::decompact)
+    }
 }
 
 impl IRData {
@@ -335,6 +398,7 @@ impl IRData {
             graph,
             input: shapes_to_outs(1, input),
             output: shapes_to_outs(1, output),
+            shared: None,
         }
     }
 
@@ -345,6 +409,7 @@ impl IRData {
             graph,
             input: Default::default(),
             output: Default::default(),
+            shared: None,
         }
     }
 }
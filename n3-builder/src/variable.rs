@@ -147,6 +147,7 @@ impl Detach for ast::RefVariable {
             description: this.description.clone(),
             ty: this.ty.clone(),
             value: this.value.clone(),
+            constraint: this.constraint.clone(),
         };
         cloned.into()
     }
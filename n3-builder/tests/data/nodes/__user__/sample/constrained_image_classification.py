@@ -0,0 +1,9 @@
+from n3 import *
+
+
+class ConstrainedImageClassification(Trainer):
+    def train(self, kwargs):
+        raise NotImplementedError
+
+    def eval(self, kwargs):
+        raise NotImplementedError
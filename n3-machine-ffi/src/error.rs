@@ -1,3 +1,5 @@
+use std::fmt;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -20,6 +22,26 @@ pub struct NetError(pub Box<dyn std::error::Error>);
 #[derive(Debug)]
 pub struct SMPError(pub Box<dyn std::error::Error>);
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueryError(e) => e.fmt(f),
+            Self::NetError(e) => write!(f, "network error: {}", e.0),
+            Self::SMPError(e) => write!(f, "smp error: {}", e.0),
+            Self::DeviceError(e) => write!(f, "device error: {}", e),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedTokens { query } => write!(f, "unexpected tokens: `{}`", query),
+            Self::EmptyMachines => write!(f, "no machines are given"),
+        }
+    }
+}
+
 impl From<QueryError> for Error {
     fn from(error: QueryError) -> Self {
         Self::QueryError(error)
@@ -2,6 +2,7 @@ mod error;
 mod handler;
 mod smp;
 
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt;
 use std::fs::{self, File};
@@ -29,6 +30,7 @@ pub struct Program {
 pub struct WorkHandler {
     signal: SignalHandler,
     status: SMPool<WorkStatus>,
+    metrics_path: PathBuf,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -37,8 +39,13 @@ pub struct WorkStatus {
     pub error_msg: Option<String>,
     pub date_begin: Option<DateTime>,
     pub date_end: Option<DateTime>,
+    /// The latest metrics reported by the work, e.g. the loss of the last epoch.
+    #[serde(default)]
+    pub metrics: Metrics,
 }
 
+pub type Metrics = BTreeMap<String, f64>;
+
 pub type WorkId = u128;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -58,6 +65,8 @@ pub type ProgramTextVec = Vec<u8>;
 pub type ProgramText = [u8];
 
 pub trait Machine {
+    fn query(&self) -> &Query;
+
     fn spawn(&mut self, program: &mut Program, handler: &SignalHandler) -> WorkStatus;
 
     fn status(&mut self) -> WorkStatus;
@@ -108,6 +117,7 @@ impl WorkHandler {
         Ok(Self {
             signal: SignalHandler::load(&id.local_signal),
             status: SMPool::create_or_open(Self::id_to_name(id))?,
+            metrics_path: Self::metrics_path(id),
         })
     }
 
@@ -115,6 +125,7 @@ impl WorkHandler {
         Ok(Self {
             signal: signal.clone(),
             status: SMPool::create_or_open(Self::id_to_name(id))?,
+            metrics_path: Self::metrics_path(id),
         })
     }
 
@@ -122,12 +133,39 @@ impl WorkHandler {
         format!("work-{}", id.work)
     }
 
+    fn metrics_path(id: &MachineIdSet) -> PathBuf {
+        let mut path = env::temp_dir();
+        path.push(format!("n3-metrics-{}.bin", id.work));
+        path
+    }
+
     pub fn is_running(&self) -> Result<bool> {
         self.status.with_inner(|x| x.is_running)
     }
 
     pub fn status(&self) -> Result<WorkStatus> {
-        self.status.with_inner(|x| x.clone())
+        let mut status = self.status.with_inner(|x| x.clone())?;
+        status.metrics = self.metrics()?;
+        Ok(status)
+    }
+
+    fn metrics(&self) -> Result<Metrics> {
+        match File::open(&self.metrics_path) {
+            Ok(file) => Ok(bincode::deserialize_from(file).map_err(NetError::from)?),
+            Err(_) => Ok(Default::default()),
+        }
+    }
+
+    /// Stores the metrics, which are given along with the status.
+    ///
+    /// Note that the metrics are stored in a file, as the shared memory cannot hold the heap.
+    pub fn update_metrics(&self, metrics: &Metrics) -> Result<()> {
+        // note: the file is replaced at once, so that the readers cannot see it partially
+        let path_tmp = self.metrics_path.with_extension("tmp");
+        let file = File::create(&path_tmp).map_err(NetError::from)?;
+        bincode::serialize_into(file, metrics).map_err(NetError::from)?;
+        fs::rename(path_tmp, &self.metrics_path).map_err(NetError::from)?;
+        Ok(())
     }
 
     pub fn update_time(&self, total_secs: i64) -> Result<()> {
@@ -161,6 +199,16 @@ impl WorkHandler {
     }
 }
 
+impl Drop for WorkHandler {
+    fn drop(&mut self) {
+        // note: only the handler which created the status removes the metrics, like the status
+        if self.status.is_owner() {
+            fs::remove_file(&self.metrics_path).ok();
+            fs::remove_file(self.metrics_path.with_extension("tmp")).ok();
+        }
+    }
+}
+
 impl Query {
     pub fn parse<R>(query: R) -> std::result::Result<Self, QueryError>
     where
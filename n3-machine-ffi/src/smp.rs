@@ -88,6 +88,11 @@ where
         &self.name
     }
 
+    /// Returns whether the pool is created by this process, and so removed on drop.
+    pub fn is_owner(&self) -> bool {
+        self.shmem.is_owner()
+    }
+
     pub fn with_inner<R>(&self, f: impl Fn(&mut SM) -> R) -> Result<R> {
         let guard = self.mutex.lock().map_err(SMPError)?;
         let inner = unsafe { &mut *(*guard as *mut SM) };
@@ -40,6 +40,15 @@ impl HostMachine {
         Ok(num_machines as u64)
     }
 
+    /// Returns the machines which would be loaded by the query, without loading them.
+    pub fn machines(&self, query: &Query) -> Vec<Query> {
+        self.get_machines(query)
+            .unwrap_or_default()
+            .iter()
+            .map(|x| x.query().clone())
+            .collect()
+    }
+
     fn get_machines(&self, query: &Query) -> Option<Vec<Box<dyn Machine>>> {
         for (pattern, generator) in &self.generators {
             if pattern.eq_weakly(query) {
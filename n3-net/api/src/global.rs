@@ -6,18 +6,6 @@ use rocket_cors::AllowedOrigins;
 use crate::db::Database;
 use crate::error::Result;
 
-pub struct ExecRoot(Mutex<n3_builder::ExecRoot>);
-
-unsafe impl Send for ExecRoot {}
-unsafe impl Sync for ExecRoot {}
-
-impl ExecRoot {
-    pub fn with<T>(&self, f: impl FnOnce(&mut n3_builder::ExecRoot) -> T) -> T {
-        let mut root = self.0.lock().unwrap();
-        f(&mut *root)
-    }
-}
-
 #[derive(Default)]
 pub struct WorkRoot(Mutex<crate::model::WorkRoot>);
 
@@ -40,14 +28,13 @@ impl WorkRoot {
 }
 
 lazy_static! {
-    pub static ref EXEC_ROOT: ExecRoot = {
+    pub static ref EXEC_ROOT: n3_builder::SharedExecRoot = {
         let config = n3_builder::ExecRootConfig {
             create_root_dir: Some(true),
         };
 
         let envs = n3_builder::GlobalVars::default();
-        let root = n3_builder::ExecRoot::try_new(envs, config).unwrap();
-        ExecRoot(Mutex::new(root))
+        n3_builder::ExecRoot::try_new(envs, config).unwrap().share()
     };
     pub static ref WORK_ROOT: WorkRoot = Default::default();
 }
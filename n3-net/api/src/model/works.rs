@@ -83,15 +83,14 @@ impl Work {
             .collect::<Result<_>>()?;
 
         // Step 2. build a program
-        let program = EXEC_ROOT.with(|root| {
-            let args = root.get(&self.exec.to_pascal_case())?;
+        let mut root = EXEC_ROOT.spawn()?;
+        let args = root.get(&self.exec.to_pascal_case())?;
 
-            for (name, value) in &self.variables {
-                args.set(name, value)?;
-            }
+        for (name, value) in &self.variables {
+            args.set(name, value)?;
+        }
 
-            args.build_with_env()
-        })?;
+        let program = args.build_with_env()?;
 
         // Step 3. spawn a work
         let work = n3_net_client::Work::spawn(&program, &self.command, &machines)?;
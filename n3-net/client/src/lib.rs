@@ -132,19 +132,8 @@ impl Work {
                 todo!();
             }
 
-            let addr = match host.domain {
-                Some(addr) => addr,
-                None => {
-                    let is_distributed = num_hosts > 1;
-                    if is_distributed {
-                        get_public_ip()?
-                    } else {
-                        get_local_ip()
-                    }
-                }
-            };
-            let addr = get_ipv4(format!("{}:{}", addr, PORT))?
-                .ok_or_else(|| Error::from("Failed to parse domain address"))?;
+            let is_distributed = num_hosts > 1;
+            let addr = get_host_addr(host.domain, is_distributed)?;
 
             let socket =
                 SocketClient::<Request, Response>::try_new(addr).map_err(NetError::from)?;
@@ -186,6 +175,54 @@ impl Drop for Work {
     }
 }
 
+/// Returns the machines which the host would load by the query, without loading them.
+pub fn machines<R>(query: R) -> Result<Vec<Query>>
+where
+    R: AsRef<str>,
+{
+    let query = Query::parse(query)?;
+    if query.provider.is_some() {
+        return Err(Error::from("providers are not supported yet"));
+    }
+
+    let addr = get_host_addr(query.domain.clone(), false)?;
+    let socket = SocketClient::<Request, Response>::try_new(addr).map_err(NetError::from)?;
+
+    let request = Request::Machines {
+        query: query.clone(),
+    };
+    let machines = socket
+        .request(&request)
+        .map_err(|x| NetError(x))?
+        .machines()
+        .map_err(Error::DeviceError)?;
+
+    // note: the machines are found in the host, so they don't know where they are
+    Ok(machines
+        .into_iter()
+        .map(|machine| Query {
+            provider: query.provider.clone(),
+            domain: query.domain.clone(),
+            ..machine
+        })
+        .collect())
+}
+
+fn get_host_addr(domain: Option<String>, is_distributed: bool) -> Result<SocketAddr> {
+    let addr = match domain {
+        Some(addr) => addr,
+        None => {
+            if is_distributed {
+                get_public_ip()?
+            } else {
+                get_local_ip()
+            }
+        }
+    };
+    get_ipv4(format!("{}:{}", addr, PORT))?
+        .ok_or_else(|| Error::from("Failed to parse domain address"))
+}
+
 fn get_public_ip() -> Result<String> {
     Ok(get_if_addrs::get_if_addrs()
         .map_err(NetError::from)?
@@ -21,4 +21,7 @@ pub enum Request {
     Terminate {
         work: WorkId,
     },
+    Machines {
+        query: Query,
+    },
 }
@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use n3_machine_ffi::{MachineId, WorkStatus};
+use n3_machine_ffi::{MachineId, Query, WorkStatus};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Response {
     Error { message: String },
     Load { num_machines: MachineId },
     Status { status: WorkStatus },
+    Machines { machines: Vec<Query> },
 }
 
 impl Response {
@@ -25,4 +26,12 @@ impl Response {
             _ => unreachable!(),
         }
     }
+
+    pub fn machines(self) -> Result<Vec<Query>, String> {
+        match self {
+            Self::Machines { machines } => Ok(machines),
+            Self::Error { message } => Err(message),
+            _ => unreachable!(),
+        }
+    }
 }
@@ -36,6 +36,9 @@ where
             Self::Terminate { work } => host
                 .terminate(work)
                 .map(|status| Response::Status { status }),
+            Self::Machines { query } => Ok(Response::Machines {
+                machines: host.machines(&query),
+            }),
         }
         // error handler
         .map_or_else(
@@ -190,6 +190,7 @@ pub struct GraphCall {
     pub inputs: Option<GraphInputs>,
     pub args: Option<Keywords>,
     pub repeat: Option<Value>,
+    pub shared: Option<String>,
 }
 
 impl GraphCall {
@@ -218,6 +219,9 @@ impl fmt::Debug for GraphCall {
         if let Some(repeat) = &self.repeat {
             write!(f, " * {:?}", repeat)?;
         }
+        if let Some(shared) = &self.shared {
+            write!(f, " @shared({})", shared)?;
+        }
         Ok(())
     }
 }
@@ -226,6 +230,7 @@ impl fmt::Debug for GraphCall {
 pub struct GraphNode {
     pub id: u64,
     pub calls: Vec<GraphCall>,
+    pub guard: Option<Value>,
     pub shapes: Option<Shapes>,
 }
 
@@ -239,6 +244,10 @@ impl<'a> fmt::Debug for FmtGuard<'a, GraphNode> {
             write!(f, "{:?} + ", value)?;
         }
 
+        if let Some(guard) = &self.guard {
+            write!(f, "? {:?} ", guard)?;
+        }
+
         if let Some(shapes) = &self.shapes {
             self.child(shapes).fmt(f)
         } else {
@@ -57,6 +57,7 @@ pub struct Variable {
 
     pub ty: Option<LetType>,
     pub value: Option<Value>,
+    pub constraint: Option<LetConstraint>,
 }
 
 impl Variable {
@@ -160,6 +161,91 @@ impl fmt::Debug for LetType {
     }
 }
 
+/// The values which a variable may have, e.g. `in 1..`, `in 0<..<1` or `in [Mnist, Cifar10]`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum LetConstraint {
+    Range {
+        min: Option<RangeBound>,
+        max: Option<RangeBound>,
+    },
+    /// The choices are stored in the formatted form, e.g. `yes`, `3` or `Mnist`.
+    Choices(Vec<String>),
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeBound {
+    pub value: f64,
+    pub exclusive: bool,
+}
+
+impl LetConstraint {
+    /// Tests whether the value satisfies the constraint.
+    ///
+    /// Note that the values which are not determined yet, such as expressions, are always accepted.
+    pub fn test(&self, value: &Value) -> bool {
+        match value {
+            Value::List(values) => values.iter().all(|x| self.test(x)),
+            Value::Variable(_) | Value::Expr(_) | Value::Dim(_) | Value::Map(_) => true,
+            _ => match self {
+                Self::Range { min, max } => match value.unwrap_real() {
+                    Some(value) => {
+                        min.map(|x| x.test(value, true)).unwrap_or(true)
+                            && max.map(|x| x.test(value, false)).unwrap_or(true)
+                    }
+                    None => false,
+                },
+                Self::Choices(choices) => {
+                    let value = format!("{:?}", value);
+                    choices.contains(&value)
+                }
+            },
+        }
+    }
+
+    /// Returns the choices, which can be given to the CLI.
+    pub fn choices(&self) -> Option<&[String]> {
+        match self {
+            Self::Range { .. } => None,
+            Self::Choices(choices) => Some(choices),
+        }
+    }
+}
+
+impl RangeBound {
+    fn test(&self, value: f64, is_min: bool) -> bool {
+        match (is_min, self.exclusive) {
+            (true, false) => self.value <= value,
+            (true, true) => self.value < value,
+            (false, false) => value <= self.value,
+            (false, true) => value < self.value,
+        }
+    }
+}
+
+impl fmt::Debug for LetConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Range { min, max } => {
+                if let Some(min) = min {
+                    write!(f, "{}", min.value)?;
+                    if min.exclusive {
+                        write!(f, "<")?;
+                    }
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    if max.exclusive {
+                        write!(f, "<")?;
+                    }
+                    write!(f, "{}", max.value)?;
+                }
+                Ok(())
+            }
+            Self::Choices(choices) => write!(f, "[{}]", choices.join(", ")),
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LetNodeType {
     Default,
@@ -180,6 +266,7 @@ pub struct NodeLet {
     pub shortcut: Option<String>,
     pub ty: LetType,
     pub value: Option<Value>,
+    pub constraint: Option<LetConstraint>,
 }
 
 crate::impl_debug_no_guard!(NodeLet);
@@ -199,6 +286,9 @@ impl<'a> fmt::Debug for FmtGuard<'a, NodeLet> {
                 None => write!(f, " *")?,
             }
         }
+        if let Some(constraint) = &self.constraint {
+            write!(f, " in {:?}", constraint)?;
+        }
         writeln!(f)
     }
 }
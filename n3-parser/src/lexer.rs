@@ -82,6 +82,7 @@ pub fn get_keywords() -> HashMap<String, Tok> {
     keywords.insert(String::from("int"), Tok::LetInt);
     keywords.insert(String::from("real"), Tok::LetReal);
     keywords.insert(String::from("dim"), Tok::LetDim);
+    keywords.insert(String::from("in"), Tok::LetIn);
     keywords.insert(String::from("node"), Tok::NodeDef);
     keywords.insert(String::from("extern"), Tok::NodeExtern);
     keywords.insert(String::from("data"), Tok::NodeData);
@@ -89,6 +90,7 @@ pub fn get_keywords() -> HashMap<String, Tok> {
     keywords.insert(String::from("exec"), Tok::NodeExec);
     keywords.insert(String::from("use"), Tok::UseDef);
     keywords.insert(String::from("by"), Tok::UseBy);
+    keywords.insert(String::from("shared"), Tok::Shared);
     keywords
 }
 
@@ -812,6 +814,12 @@ where
                 if let Some('0'..='9') = self.chr1 {
                     let number = self.lex_number()?;
                     self.emit(number);
+                } else if let Some('.') = self.chr1 {
+                    let tok_start = self.get_pos();
+                    self.next_char();
+                    self.next_char();
+                    let tok_end = self.get_pos();
+                    self.emit((tok_start, Tok::Range, tok_end));
                 } else {
                     self.eat_single_char(Tok::Dot);
                 }
@@ -886,6 +894,12 @@ where
             '$' => {
                 self.eat_single_char(Tok::NodeIdx);
             }
+            '@' => {
+                self.eat_single_char(Tok::At);
+            }
+            '?' => {
+                self.eat_single_char(Tok::Question);
+            }
             '+' => {
                 self.eat_single_char(Tok::Add);
             }
@@ -919,6 +933,9 @@ where
             '|' => {
                 self.eat_single_char(Tok::Or);
             }
+            '<' => {
+                self.eat_single_char(Tok::Less);
+            }
             _ => {
                 let c = self.next_char();
                 return Err(LexicalError {
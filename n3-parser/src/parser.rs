@@ -31,3 +31,82 @@ impl Parser {
         self.inner.parse(tokenizer).map_err(ParseError::from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_lets(lets: &str) -> ast::Node {
+        let source = format!("node Foo:\n{}\n    1. Relu\n", lets);
+        Parser::default().parse_file(&source).unwrap().node
+    }
+
+    fn bound(value: f64, exclusive: bool) -> Option<ast::RangeBound> {
+        Some(ast::RangeBound { value, exclusive })
+    }
+
+    #[test]
+    fn test_let_constraints() {
+        let node = parse_lets(
+            "    let epoch = int * in 1..
+    let lr = real 0.001 in 0<..<1
+    let axis = int -1 in -1..<4
+    let scale = real * in ..2.5
+    let D: data = data node * in [Mnist, Cifar10]
+    let ratio = real * in [0.5, 1]
+",
+        );
+
+        let constraint = |name: &str| node.graph[name].constraint.clone().unwrap();
+        assert_eq!(
+            constraint("epoch"),
+            ast::LetConstraint::Range {
+                min: bound(1.0, false),
+                max: None,
+            }
+        );
+        assert_eq!(
+            constraint("lr"),
+            ast::LetConstraint::Range {
+                min: bound(0.0, true),
+                max: bound(1.0, true),
+            }
+        );
+        assert_eq!(
+            constraint("axis"),
+            ast::LetConstraint::Range {
+                min: bound(-1.0, false),
+                max: bound(4.0, true),
+            }
+        );
+        assert_eq!(
+            constraint("scale"),
+            ast::LetConstraint::Range {
+                min: None,
+                max: bound(2.5, false),
+            }
+        );
+        assert_eq!(
+            constraint("data"),
+            ast::LetConstraint::Choices(vec!["Mnist".to_string(), "Cifar10".to_string()])
+        );
+        assert_eq!(
+            constraint("ratio"),
+            ast::LetConstraint::Choices(vec!["0.5".to_string(), "1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_let_in_name() {
+        // note: `in` is a keyword only after the value
+        let node = parse_lets(
+            "    let zoom in = int 3
+    let in = real * in 0..
+",
+        );
+
+        assert_eq!(node.graph["zoom in"].value, Some(ast::Value::Int(3)));
+        assert_eq!(node.graph["zoom in"].constraint, None);
+        assert!(node.graph["in"].constraint.is_some());
+    }
+}
@@ -27,7 +27,11 @@ pub enum Tok {
     Colon,
     Equal,
     NodeIdx,
+    At,
+    Question,
     Dot,
+    Range,
+    Less,
     Add,
     Sub,
     Mul,
@@ -46,6 +50,7 @@ pub enum Tok {
     LetInt,
     LetReal,
     LetDim,
+    LetIn,
     NodeDef,
     NodeExtern,
     NodeData,
@@ -53,6 +58,7 @@ pub enum Tok {
     NodeExec,
     UseDef,
     UseBy,
+    Shared,
 }
 
 impl fmt::Display for Tok {
@@ -94,7 +100,11 @@ impl fmt::Display for Tok {
             Colon => f.write_str("':'"),
             Equal => f.write_str("'='"),
             NodeIdx => f.write_str("'$'"),
+            At => f.write_str("'@'"),
+            Question => f.write_str("'?'"),
             Dot => f.write_str("'.'"),
+            Range => f.write_str("'..'"),
+            Less => f.write_str("'<'"),
             Add => f.write_str("'+'"),
             Sub => f.write_str("'-'"),
             Mul => f.write_str("'*'"),
@@ -113,6 +123,7 @@ impl fmt::Display for Tok {
             LetInt => f.write_str("'int'"),
             LetReal => f.write_str("'real'"),
             LetDim => f.write_str("'dim'"),
+            LetIn => f.write_str("'in'"),
             NodeDef => f.write_str("'node'"),
             NodeExtern => f.write_str("'extern'"),
             NodeData => f.write_str("'data'"),
@@ -120,6 +131,7 @@ impl fmt::Display for Tok {
             NodeExec => f.write_str("'exec'"),
             UseDef => f.write_str("'use'"),
             UseBy => f.write_str("'by'"),
+            Shared => f.write_str("'shared'"),
         }
     }
 }
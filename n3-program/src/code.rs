@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
+
 use crate::ast;
 use crate::externs::ExternCode;
 use crate::graph::Table;
@@ -67,4 +69,18 @@ pub struct CodeData {
     pub graph: Table,
     pub input: ast::Outs,
     pub output: ast::Outs,
+    pub shared: Option<String>,
+}
+
+/// The resolved dimensions of a tensor, where the unknown ones are `None`.
+pub type Shape = Vec<Option<u64>>;
+
+/// The shapes of the tensors, keyed by their names.
+pub type Shapes = BTreeMap<String, Option<Shape>>;
+
+/// The resolved input and output shapes of a code.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CodeShapes {
+    pub input: Shapes,
+    pub output: Shapes,
 }
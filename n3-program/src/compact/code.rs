@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use super::code_extern::ExternCode;
 use super::code_node::NodeCode;
-use super::graph::Graphs;
+use super::graph::IdArranger;
 use super::{ArrangeId, Compact, CompactContext, Decompact, DecompactContext};
 use crate::ast;
 
@@ -28,7 +28,7 @@ impl Compact for crate::code::Code {
 }
 
 impl ArrangeId for Code {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         match self {
             Self::Node(x) => x.arrange_id(ids),
             Self::Extern(x) => x.arrange_id(ids),
@@ -54,6 +54,7 @@ pub struct CodeData {
     pub graph: u64,
     pub input: ast::Outs,
     pub output: ast::Outs,
+    pub shared: Option<String>,
 }
 
 impl Compact for crate::code::CodeData {
@@ -65,13 +66,14 @@ impl Compact for crate::code::CodeData {
             graph: self.graph.compact(ctx),
             input: self.input.clone(),
             output: self.output.clone(),
+            shared: self.shared.clone(),
         }
     }
 }
 
 impl ArrangeId for CodeData {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
-        self.graph = ids[&self.graph];
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
+        self.graph = ids.arrange(self.graph);
     }
 }
 
@@ -85,6 +87,7 @@ impl Decompact for CodeData {
             graph: ctx.get_graph(self.graph).clone(),
             input: self.input,
             output: self.output,
+            shared: self.shared,
         }
     }
 }
@@ -1,14 +1,14 @@
 use serde::{Deserialize, Serialize};
 
 use super::code::CodeData;
-use super::graph::Graphs;
+use super::graph::IdArranger;
 use super::{ArrangeId, Compact, CompactContext, Decompact, DecompactContext};
 use crate::ast;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExternCode {
-    ty: ast::ExternNodeType,
-    data: CodeData,
+    pub(super) ty: ast::ExternNodeType,
+    pub(super) data: CodeData,
 }
 
 impl Compact for crate::externs::ExternCode {
@@ -23,7 +23,7 @@ impl Compact for crate::externs::ExternCode {
 }
 
 impl ArrangeId for ExternCode {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.data.arrange_id(ids);
     }
 }
@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
+
 use super::code::Codes;
 use super::exec::Program;
 use super::graph::{Env, Graphs, Table};
 use super::value::Values;
-use super::{ArrangeId, Decompact};
+use super::Decompact;
 use crate::ast;
+use crate::code::CodeShapes;
 use crate::externs::PythonScripts;
 
 pub struct CompactContext {
@@ -11,6 +14,7 @@ pub struct CompactContext {
     graphs: Graphs<Table>,
     pub nodes: Codes,
     scripts: PythonScripts,
+    pub(super) shapes: BTreeMap<String, CodeShapes>,
 }
 
 impl CompactContext {
@@ -20,6 +24,7 @@ impl CompactContext {
             graphs: Graphs::new(),
             nodes: Codes::new(),
             scripts,
+            shapes: BTreeMap::new(),
         }
     }
 
@@ -31,15 +36,20 @@ impl CompactContext {
         self.graphs.insert(id, graph);
     }
 
+    /// Returns the compacted graphs, without arranging their ids.
+    pub fn into_graphs(self) -> Graphs<Table> {
+        self.graphs
+    }
+
     pub fn build(mut self) -> Program {
-        let (ids, graphs) = self.graphs.arrange_id();
-        self.nodes.arrange_id(&ids);
+        let graphs = self.graphs.arrange_id(&mut self.nodes);
 
         Program {
             env: self.env,
             graphs,
             nodes: self.nodes,
             scripts: self.scripts,
+            shapes: self.shapes,
         }
     }
 }
@@ -1,12 +1,17 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::io;
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use super::code::Codes;
 use super::graph::{Env, Table, UncompactedEnv};
+use super::header::{Header, FLAGS_DEFAULT, FLAG_DEDUP_SCRIPTS};
 use super::{Compact, CompactContext, Decompact, DecompactContext};
-use crate::error::Result;
-use crate::externs::PythonScripts;
+use crate::code::CodeShapes;
+use crate::error::{Error, Result};
+use crate::externs::{PythonScript, PythonScripts};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Program {
@@ -14,6 +19,8 @@ pub struct Program {
     pub(super) graphs: Vec<Table>,
     pub(super) nodes: Codes,
     pub(super) scripts: PythonScripts,
+    #[serde(default)]
+    pub(super) shapes: BTreeMap<String, CodeShapes>,
 }
 
 impl Program {
@@ -24,6 +31,7 @@ impl Program {
         UncompactedEnv(&program.env).compact(&mut ctx);
         program.graph.compact(&mut ctx);
         ctx.nodes = program.nodes.compact(&mut ctx);
+        ctx.shapes = program.shapes.clone();
         ctx.build()
     }
 
@@ -45,6 +53,7 @@ impl Program {
             graph,
             nodes,
             scripts: self.scripts,
+            shapes: self.shapes,
         }
     }
 
@@ -52,14 +61,123 @@ impl Program {
     where
         W: io::Write,
     {
-        bincode::serialize_into(writer, self).map_err(|e| e.into())
+        self.save_with_flags(writer, FLAGS_DEFAULT)
     }
 
-    pub(crate) fn load<R>(reader: R) -> Result<Program>
+    pub fn save_with_flags<W>(&self, mut writer: W, flags: u32) -> Result<()>
+    where
+        W: io::Write,
+    {
+        let payload = if flags & FLAG_DEDUP_SCRIPTS != 0 {
+            bincode::serialize(&DedupedProgram::from(self.clone()))?
+        } else {
+            bincode::serialize(self)?
+        };
+        let binary = Header::encode(payload, flags)?;
+        writer.write_all(&binary).map_err(|e| e.into())
+    }
+
+    /// Returns the SHA-256 hash of the program, except the env.
+    pub fn digest(&self) -> Result<String> {
+        let binary = bincode::serialize(&(&self.graphs, &self.nodes, &self.scripts))?;
+        let hash = Sha256::digest(&binary);
+        Ok(hash.iter().map(|x| format!("{:02x}", x)).collect())
+    }
+
+    pub(crate) fn load<R>(mut reader: R) -> Result<Program>
     where
         R: io::Read,
     {
-        let program: Self = bincode::deserialize_from(reader)?;
-        Ok(program)
+        let mut binary = vec![];
+        reader.read_to_end(&mut binary)?;
+
+        let (header, payload) = Header::read(&binary)?;
+        let is_legacy = header.format_version == 0;
+        let payload = match header.migrate(payload) {
+            Ok(payload) => payload,
+            // note: the legacy binaries cannot be distinguished from the others
+            Err(_) if is_legacy => return Err(Error::NotProgram),
+            Err(e) => return Err(e),
+        };
+
+        let program = if header.flags & FLAG_DEDUP_SCRIPTS != 0 {
+            bincode::deserialize::<DedupedProgram>(&payload).map(Program::try_from)
+        } else {
+            bincode::deserialize(&payload).map(Ok)
+        };
+        match program {
+            Ok(program) => program,
+            // note: the legacy binaries cannot be distinguished from the others
+            Err(_) if is_legacy => Err(Error::NotProgram),
+            Err(e) => Err(e.into()),
+        }
     }
 }
+
+/// The program whose scripts refer to their sources by the hashes.
+#[derive(Serialize, Deserialize)]
+struct DedupedProgram {
+    env: Option<Env>,
+    graphs: Vec<Table>,
+    nodes: Codes,
+    // key -> (name, hash)
+    scripts: BTreeMap<String, (String, u64)>,
+    // hash -> source
+    sources: BTreeMap<u64, String>,
+    shapes: BTreeMap<String, CodeShapes>,
+}
+
+impl From<Program> for DedupedProgram {
+    fn from(program: Program) -> Self {
+        let mut scripts = BTreeMap::new();
+        let mut sources = BTreeMap::new();
+        for (key, script) in program.scripts {
+            let hash = hash_source(&script.source);
+            scripts.insert(key, (script.name, hash));
+            sources.entry(hash).or_insert(script.source);
+        }
+
+        Self {
+            env: program.env,
+            graphs: program.graphs,
+            nodes: program.nodes,
+            scripts,
+            sources,
+            shapes: program.shapes,
+        }
+    }
+}
+
+impl TryFrom<DedupedProgram> for Program {
+    type Error = Error;
+
+    fn try_from(program: DedupedProgram) -> Result<Self> {
+        let sources = program.sources;
+        let scripts = program
+            .scripts
+            .into_iter()
+            .map(|(key, (name, hash))| {
+                let source = match sources.get(&hash) {
+                    Some(source) => source.clone(),
+                    None => return Err(Error::MissingScriptSource { name }),
+                };
+                Ok((key, PythonScript { name, source }))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            env: program.env,
+            graphs: program.graphs,
+            nodes: program.nodes,
+            scripts,
+            shapes: program.shapes,
+        })
+    }
+}
+
+fn hash_source(source: &str) -> u64 {
+    let hash = Sha256::digest(source.as_bytes());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(bytes)
+}
@@ -3,6 +3,7 @@ use std::ops::{Deref, DerefMut};
 
 use serde::{Deserialize, Serialize};
 
+use super::code::Codes;
 use super::value::Values;
 use super::variable::{VarAsKey, VariableKey};
 use super::{ArrangeId, Compact, CompactContext, Decompact, DecompactContext};
@@ -51,18 +52,53 @@ impl Decompact for Vec<Table> {
 }
 
 impl Graphs<Table> {
-    pub fn arrange_id(mut self) -> (Graphs<u64>, Vec<Table>) {
-        let ids = Graphs(
-            self.0
-                .keys()
-                .enumerate()
-                .map(|(v, k)| (*k, v as u64))
-                .collect(),
-        );
-        self.0.arrange_id(&ids);
-
-        let graphs = self.0.into_iter().map(|(_, x)| x).collect();
-        (ids, graphs)
+    /// Renumbers the graphs in the order of their first appearance, from the main graph.
+    ///
+    /// The ids given while building depend on the order of building the nodes,
+    /// so the same program should have the same ids regardless of it.
+    pub fn arrange_id(mut self, nodes: &mut Codes) -> Vec<Table> {
+        let mut ids = IdArranger::default();
+        let mut graphs: Vec<Table> = vec![];
+
+        // note: ordered (main graph -> nodes -> the graphs referred by the graphs)
+        ids.arrange(0);
+        nodes.arrange_id(&mut ids);
+
+        loop {
+            let id = match ids.order.get(graphs.len()) {
+                Some(&id) => id,
+                // note: the graphs which are never referred are placed at last
+                None => match self.0.keys().next() {
+                    Some(&id) => {
+                        ids.arrange(id);
+                        id
+                    }
+                    None => break,
+                },
+            };
+
+            let mut table = self.0.remove(&id).unwrap();
+            table.arrange_id(&mut ids);
+            graphs.push(table);
+        }
+        graphs
+    }
+}
+
+/// Assigns the new ids to the graphs in the order of their first appearance.
+#[derive(Debug, Default)]
+pub struct IdArranger {
+    ids: BTreeMap<u64, u64>,
+    order: Vec<u64>,
+}
+
+impl IdArranger {
+    pub fn arrange(&mut self, id: u64) -> u64 {
+        let order = &mut self.order;
+        *self.ids.entry(id).or_insert_with(|| {
+            order.push(id);
+            order.len() as u64 - 1
+        })
     }
 }
 
@@ -84,7 +120,7 @@ impl DerefMut for Table {
 }
 
 impl ArrangeId for Table {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.0.arrange_id(ids)
     }
 }
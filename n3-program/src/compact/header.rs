@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::code::CodeShapes;
+use crate::error::{Error, Result};
+
+/// The magic bytes which start every program binary.
+pub const MAGIC: &[u8; 4] = b"N3PG";
+
+/// The current version of the layout of the program binary.
+///
+/// Increase it with a new migration when the layout is changed.
+pub const FORMAT_VERSION: u16 = MIGRATIONS.len() as u16;
+
+/// The payload is compressed with deflate.
+pub const FLAG_DEFLATE: u32 = 1 << 0;
+/// The sources of the scripts are stored once per content.
+pub const FLAG_DEDUP_SCRIPTS: u32 = 1 << 1;
+
+/// The flags which are understood by this version.
+pub const FLAGS_SUPPORTED: u32 = FLAG_DEFLATE | FLAG_DEDUP_SCRIPTS;
+/// The flags which are used by default.
+pub const FLAGS_DEFAULT: u32 = FLAG_DEFLATE | FLAG_DEDUP_SCRIPTS;
+
+/// Upgrades the payload of a format version to the next one.
+pub type Migration = fn(Vec<u8>) -> Result<Vec<u8>>;
+
+/// The migrations, where the n-th one upgrades the format version n to n + 1.
+const MIGRATIONS: &[Migration] = &[
+    // 0 -> 1: the legacy binaries without the header have no `shared` tags in the codes
+    super::legacy::migrate_v0,
+    // 1 -> 2: the shapes of the nodes are appended, which are empty before
+    |mut payload| {
+        let shapes: BTreeMap<String, CodeShapes> = BTreeMap::new();
+        payload.extend_from_slice(&bincode::serialize(&shapes)?);
+        Ok(payload)
+    },
+];
+
+/// The self-describing header of the program binary, which follows the magic bytes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub format_version: u16,
+    /// The version of n3 which wrote the binary.
+    pub builder_version: String,
+    pub flags: u32,
+    pub checksum: u64,
+}
+
+impl Header {
+    pub fn new(payload: &[u8], flags: u32) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            builder_version: env!("CARGO_PKG_VERSION").to_string(),
+            flags,
+            checksum: checksum(payload),
+        }
+    }
+
+    /// Returns the header of a legacy binary, which has no magic bytes.
+    fn legacy(payload: &[u8]) -> Self {
+        Self {
+            format_version: 0,
+            builder_version: String::new(),
+            flags: 0,
+            checksum: checksum(payload),
+        }
+    }
+
+    /// Compresses the payload if flagged, and writes it with the header.
+    pub fn encode(payload: Vec<u8>, flags: u32) -> Result<Vec<u8>> {
+        let payload = if flags & FLAG_DEFLATE != 0 {
+            let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+            encoder.write_all(&payload)?;
+            encoder.finish()?
+        } else {
+            payload
+        };
+        Self::new(&payload, flags).write(&payload)
+    }
+
+    /// Writes the magic bytes, the header and the payload.
+    pub fn write(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut binary = MAGIC.to_vec();
+        bincode::serialize_into(&mut binary, self)?;
+        binary.extend_from_slice(payload);
+        Ok(binary)
+    }
+
+    /// Splits the binary into the header and the payload, without validating them.
+    pub fn read(binary: &[u8]) -> Result<(Self, &[u8])> {
+        match binary.strip_prefix(MAGIC) {
+            Some(mut binary) => {
+                let header =
+                    bincode::deserialize_from(&mut binary).map_err(|_| Error::InvalidHeader)?;
+                Ok((header, binary))
+            }
+            None => Ok((Self::legacy(binary), binary)),
+        }
+    }
+
+    /// Validates and decompresses the payload, and upgrades it to the current format version.
+    pub fn migrate(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        if self.format_version > FORMAT_VERSION {
+            return Err(Error::UnsupportedFormatVersion {
+                given: self.format_version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        if self.flags & !FLAGS_SUPPORTED != 0 {
+            return Err(Error::UnsupportedFlags {
+                flags: self.flags & !FLAGS_SUPPORTED,
+            });
+        }
+
+        let checksum = checksum(payload);
+        if self.checksum != checksum {
+            return Err(Error::MismatchedChecksum {
+                expected: self.checksum,
+                given: checksum,
+            });
+        }
+
+        let payload = if self.flags & FLAG_DEFLATE != 0 {
+            let mut decompressed = vec![];
+            DeflateDecoder::new(payload).read_to_end(&mut decompressed)?;
+            decompressed
+        } else {
+            payload.to_vec()
+        };
+
+        MIGRATIONS[self.format_version as usize..]
+            .iter()
+            .try_fold(payload, |payload, migrate| migrate(payload))
+    }
+}
+
+fn checksum(payload: &[u8]) -> u64 {
+    let hash = Sha256::digest(payload);
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::code::{self, Codes};
+use super::code_extern::ExternCode;
+use super::code_node::NodeCode;
+use super::graph::{Env, Table};
+use crate::ast;
+use crate::error::Result;
+use crate::externs::PythonScripts;
+
+/// The program of the format version 0, which was written without the header.
+///
+/// Its codes have no `shared` tags, as the parameter sharing is not supported yet.
+#[derive(Deserialize)]
+struct ProgramV0 {
+    env: Option<Env>,
+    graphs: Vec<Table>,
+    nodes: BTreeMap<String, CodeV0>,
+    scripts: PythonScripts,
+}
+
+/// The program of the format version 1, which is followed by the next migrations.
+#[derive(Serialize)]
+struct ProgramV1 {
+    env: Option<Env>,
+    graphs: Vec<Table>,
+    nodes: Codes,
+    scripts: PythonScripts,
+}
+
+#[derive(Deserialize)]
+enum CodeV0 {
+    Node {
+        data: CodeDataV0,
+        tensor_graph: Vec<CodeV0>,
+    },
+    Extern {
+        ty: ast::ExternNodeType,
+        data: CodeDataV0,
+    },
+}
+
+#[derive(Deserialize)]
+struct CodeDataV0 {
+    name: String,
+    graph: u64,
+    input: ast::Outs,
+    output: ast::Outs,
+}
+
+impl From<CodeV0> for code::Code {
+    fn from(code: CodeV0) -> Self {
+        match code {
+            CodeV0::Node { data, tensor_graph } => Self::Node(NodeCode {
+                data: data.into(),
+                tensor_graph: tensor_graph.into_iter().map(Into::into).collect(),
+            }),
+            CodeV0::Extern { ty, data } => Self::Extern(ExternCode {
+                ty,
+                data: data.into(),
+            }),
+        }
+    }
+}
+
+impl From<CodeDataV0> for code::CodeData {
+    fn from(data: CodeDataV0) -> Self {
+        Self {
+            name: data.name,
+            graph: data.graph,
+            input: data.input,
+            output: data.output,
+            shared: None,
+        }
+    }
+}
+
+/// Upgrades the payload of the format version 0 to 1, where the codes have the `shared` tags.
+pub fn migrate_v0(payload: Vec<u8>) -> Result<Vec<u8>> {
+    let program: ProgramV0 = bincode::deserialize(&payload)?;
+    let program = ProgramV1 {
+        env: program.env,
+        graphs: program.graphs,
+        nodes: program
+            .nodes
+            .into_iter()
+            .map(|(name, code)| (name, code.into()))
+            .collect(),
+        scripts: program.scripts,
+    };
+    Ok(bincode::serialize(&program)?)
+}
@@ -4,15 +4,22 @@ mod code_node;
 mod context;
 mod exec;
 mod graph;
+mod header;
+mod legacy;
+mod text;
 mod value;
 mod variable;
 
 use std::collections::BTreeMap;
 
-use self::graph::Graphs;
-
 pub use self::context::{CompactContext, DecompactContext};
 pub use self::exec::Program;
+pub use self::graph::{Graphs, IdArranger, Table};
+pub use self::header::{
+    Header, Migration, FLAGS_DEFAULT, FLAGS_SUPPORTED, FLAG_DEDUP_SCRIPTS, FLAG_DEFLATE,
+    FORMAT_VERSION, MAGIC,
+};
+pub use self::value::Value;
 
 pub trait Compact {
     type Output;
@@ -21,7 +28,7 @@ pub trait Compact {
 }
 
 pub trait ArrangeId {
-    fn arrange_id(&mut self, ids: &Graphs<u64>);
+    fn arrange_id(&mut self, ids: &mut IdArranger);
 }
 
 pub trait Decompact {
@@ -50,7 +57,7 @@ where
     K: Clone + Ord,
     V: ArrangeId,
 {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.values_mut().for_each(|x| x.arrange_id(ids))
     }
 }
@@ -85,7 +92,7 @@ impl<T> ArrangeId for Vec<T>
 where
     T: ArrangeId,
 {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.iter_mut().for_each(|x| x.arrange_id(ids))
     }
 }
@@ -117,7 +124,7 @@ impl<T> ArrangeId for Option<T>
 where
     T: ArrangeId,
 {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         if let Some(x) = self.as_mut() {
             x.arrange_id(ids);
         }
@@ -151,7 +158,7 @@ impl<T> ArrangeId for Box<T>
 where
     T: ArrangeId,
 {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         (**self).arrange_id(ids)
     }
 }
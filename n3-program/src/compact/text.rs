@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::code::Codes;
+use super::exec::Program;
+use super::graph::{Env, Table};
+use crate::code::CodeShapes;
+use crate::error::{Error, Result};
+use crate::externs::PythonScripts;
+
+/// The human-readable form of the program, which is used for debugging and auditing.
+///
+/// The main graph is separated from the other graphs, which are keyed by their ids.
+#[derive(Serialize, Deserialize)]
+struct ProgramText {
+    env: Option<Env>,
+    graph: Table,
+    graphs: BTreeMap<u64, Table>,
+    nodes: Codes,
+    scripts: PythonScripts,
+    #[serde(default)]
+    shapes: BTreeMap<String, CodeShapes>,
+}
+
+impl From<Program> for ProgramText {
+    fn from(program: Program) -> Self {
+        let mut graphs = program.graphs.into_iter().enumerate();
+        let (_, graph) = graphs
+            .next()
+            .expect("the program should have the main graph");
+
+        Self {
+            env: program.env,
+            graph,
+            graphs: graphs.map(|(id, graph)| (id as u64, graph)).collect(),
+            nodes: program.nodes,
+            scripts: program.scripts,
+            shapes: program.shapes,
+        }
+    }
+}
+
+impl ProgramText {
+    fn into_program(self) -> Result<Program> {
+        let mut graphs = vec![self.graph];
+        for (id, graph) in self.graphs {
+            // note: the ids should be the indices of the graphs
+            if id != graphs.len() as u64 {
+                return Err(Error::InvalidGraphId { id });
+            }
+            graphs.push(graph);
+        }
+
+        Ok(Program {
+            env: self.env,
+            graphs,
+            nodes: self.nodes,
+            scripts: self.scripts,
+            shapes: self.shapes,
+        })
+    }
+}
+
+impl Program {
+    pub fn to_json(&self) -> Result<String> {
+        let text = ProgramText::from(self.clone());
+        Ok(serde_json::to_string_pretty(&text)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str::<ProgramText>(text)?.into_program()
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        let text = ProgramText::from(self.clone());
+        Ok(serde_yaml::to_string(&text)?)
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        serde_yaml::from_str::<ProgramText>(text)?.into_program()
+    }
+}
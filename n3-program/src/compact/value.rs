@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
-use super::graph::Graphs;
+use super::graph::IdArranger;
 use super::variable::{VarAsValue, VariableValue};
 use super::{ArrangeId, Compact, CompactContext, Decompact, DecompactContext};
 use crate::ast;
@@ -51,7 +51,7 @@ impl Compact for ast::Value {
 }
 
 impl ArrangeId for Value {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         match self {
             Self::Variable(x) => x.arrange_id(ids),
             Self::Expr(x) => x.arrange_id(ids),
@@ -83,7 +83,7 @@ impl Decompact for Value {
 }
 
 impl ArrangeId for ValueList {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.0.arrange_id(ids)
     }
 }
@@ -98,7 +98,7 @@ impl Decompact for ValueList {
 }
 
 impl ArrangeId for ValueMap {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.0.arrange_id(ids)
     }
 }
@@ -132,7 +132,7 @@ impl Compact for ast::Expr {
 }
 
 impl ArrangeId for Expr {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.lhs.arrange_id(ids);
         self.rhs.arrange_id(ids);
     }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use super::graph::Graphs;
+use super::graph::IdArranger;
 use super::value::Value;
 use super::{ArrangeId, Compact, CompactContext, Decompact, DecompactContext};
 use crate::ast;
@@ -42,7 +42,7 @@ impl<'a> Compact for VarAsKey<'a> {
 }
 
 impl ArrangeId for VariableKey {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
         self.value.arrange_id(ids)
     }
 }
@@ -75,8 +75,8 @@ impl<'a> Compact for VarAsValue<'a> {
 }
 
 impl ArrangeId for VariableValue {
-    fn arrange_id(&mut self, ids: &Graphs<u64>) {
-        self.id = ids[&self.id];
+    fn arrange_id(&mut self, ids: &mut IdArranger) {
+        self.id = ids.arrange(self.id);
     }
 }
 
@@ -0,0 +1,308 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::ast;
+use crate::code::{Code, CodeData, CodeShapes, Shape, Shapes};
+use crate::execs::Program;
+use crate::graph::{ToValues, Values};
+
+/// A change between two programs, addressed by the path of the item.
+///
+/// The paths are joined with `.`, and the nodes in a tensor graph are addressed by
+/// their names and the order of appearance, e.g. `nodes.model.tensor_graph.Conv2D#1.graph.stride`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added(String),
+    Removed(String),
+    Changed { from: String, to: String },
+    Moved { from: usize, to: usize },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added(value) => write!(f, "+ {}: {}", &self.path, Summary(value)),
+            ChangeKind::Removed(value) => write!(f, "- {}: {}", &self.path, Summary(value)),
+            ChangeKind::Changed { from, to } => {
+                write!(f, "~ {}: {} -> {}", &self.path, Summary(from), Summary(to))
+            }
+            ChangeKind::Moved { from, to } => write!(f, "> {}: #{} -> #{}", &self.path, from, to),
+        }
+    }
+}
+
+/// Shortens the multi-line values such as the script sources.
+struct Summary<'a>(&'a str);
+
+impl<'a> fmt::Display for Summary<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.lines().count() {
+            0 | 1 => write!(f, "{}", self.0),
+            lines => write!(f, "({} lines)", lines),
+        }
+    }
+}
+
+impl Program {
+    /// Compares the programs, and returns the changes from `self` to `other`.
+    ///
+    /// Note that the env is not compared.
+    pub fn diff(&self, other: &Self) -> Vec<Change> {
+        let mut diff = Diff::default();
+
+        diff.values("graph", &self.graph.to_values(), &other.graph.to_values());
+        diff.map(
+            "nodes",
+            &self.nodes,
+            &other.nodes,
+            |code| code.data().name.clone(),
+            |diff, path, a, b| diff.code(path, a, b),
+        );
+        diff.map(
+            "scripts",
+            &self.scripts,
+            &other.scripts,
+            |script| script.source.clone(),
+            |diff, path, a, b| {
+                diff.value(&format!("{}.name", path), &a.name, &b.name);
+                diff.value(&format!("{}.source", path), &a.source, &b.source);
+            },
+        );
+        diff.map(
+            "shapes",
+            &self.shapes,
+            &other.shapes,
+            fmt_code_shapes,
+            |diff, path, a, b| {
+                diff.shapes(&format!("{}.input", path), &a.input, &b.input);
+                diff.shapes(&format!("{}.output", path), &a.output, &b.output);
+            },
+        );
+        diff.changes
+    }
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    fn push(&mut self, path: &str, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    fn value(&mut self, path: &str, a: &str, b: &str) {
+        if a != b {
+            let kind = ChangeKind::Changed {
+                from: a.to_string(),
+                to: b.to_string(),
+            };
+            self.push(path, kind);
+        }
+    }
+
+    fn map<T>(
+        &mut self,
+        path: &str,
+        a: &BTreeMap<String, T>,
+        b: &BTreeMap<String, T>,
+        fmt: impl Fn(&T) -> String,
+        mut f: impl FnMut(&mut Self, &str, &T, &T),
+    ) {
+        let keys: BTreeSet<_> = a.keys().chain(b.keys()).collect();
+        for key in keys {
+            let path = format!("{}.{}", path, key);
+            match (a.get(key), b.get(key)) {
+                (Some(a), Some(b)) => f(self, &path, a, b),
+                (Some(a), None) => self.push(&path, ChangeKind::Removed(fmt(a))),
+                (None, Some(b)) => self.push(&path, ChangeKind::Added(fmt(b))),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+
+    fn values(&mut self, path: &str, a: &Values, b: &Values) {
+        self.map(path, a, b, fmt_value, |diff, path, a, b| {
+            diff.value(path, &fmt_value(a), &fmt_value(b))
+        });
+    }
+
+    fn outs(&mut self, path: &str, a: &ast::Outs, b: &ast::Outs) {
+        self.map(path, a, b, fmt_out, |diff, path, a, b| {
+            diff.value(path, &fmt_out(a), &fmt_out(b))
+        });
+    }
+
+    fn shapes(&mut self, path: &str, a: &Shapes, b: &Shapes) {
+        self.map(path, a, b, fmt_shape, |diff, path, a, b| {
+            diff.value(path, &fmt_shape(a), &fmt_shape(b))
+        });
+    }
+
+    fn code(&mut self, path: &str, a: &Code, b: &Code) {
+        // note: the different nodes cannot be compared in detail
+        if a.ty() != b.ty() || a.data().name != b.data().name {
+            let kind = ChangeKind::Changed {
+                from: fmt_code(a),
+                to: fmt_code(b),
+            };
+            return self.push(path, kind);
+        }
+
+        self.data(path, a.data(), b.data());
+        match (a, b) {
+            (Code::Node(a), Code::Node(b)) => self.tensor_graph(
+                &format!("{}.tensor_graph", path),
+                &a.tensor_graph,
+                &b.tensor_graph,
+            ),
+            (Code::Extern(a), Code::Extern(b)) => {
+                self.value(&format!("{}.ty", path), &fmt_ty(a.ty), &fmt_ty(b.ty))
+            }
+            _ => unreachable!("The code types should be equal."),
+        }
+    }
+
+    fn data(&mut self, path: &str, a: &CodeData, b: &CodeData) {
+        self.values(
+            &format!("{}.graph", path),
+            &a.graph.to_values(),
+            &b.graph.to_values(),
+        );
+        self.outs(&format!("{}.input", path), &a.input, &b.input);
+        self.outs(&format!("{}.output", path), &a.output, &b.output);
+        self.value(
+            &format!("{}.shared", path),
+            &format!("{:?}", &a.shared),
+            &format!("{:?}", &b.shared),
+        );
+    }
+
+    fn tensor_graph(&mut self, path: &str, a: &[Code], b: &[Code]) {
+        let a = index_nodes(a);
+        let b = index_nodes(b);
+
+        // the order of the nodes which are in both graphs
+        let order_a = order_common(&a, &b);
+        let order_b = order_common(&b, &a);
+
+        let mut keys: Vec<_> = a.keys().chain(b.keys()).collect();
+        keys.sort_by_key(|k| (b.get(*k).or_else(|| a.get(*k)).unwrap().0, *k));
+        keys.dedup();
+
+        for key in keys {
+            let path = format!("{}.{}", path, key);
+            match (a.get(key), b.get(key)) {
+                (Some((index_a, a)), Some((index_b, b))) => {
+                    if order_a[key.as_str()] != order_b[key.as_str()] {
+                        let kind = ChangeKind::Moved {
+                            from: *index_a,
+                            to: *index_b,
+                        };
+                        self.push(&path, kind);
+                    }
+                    self.code(&path, a, b);
+                }
+                (Some((_, a)), None) => self.push(&path, ChangeKind::Removed(fmt_code(a))),
+                (None, Some((_, b))) => self.push(&path, ChangeKind::Added(fmt_code(b))),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+}
+
+/// Names the nodes with the order of appearance of the same names, e.g. `Relu#1`.
+fn index_nodes(nodes: &[Code]) -> BTreeMap<String, (usize, &Code)> {
+    let mut counts = BTreeMap::new();
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| {
+            let name = &node.data().name;
+            let count = counts.entry(name).or_insert(0);
+            *count += 1;
+            (format!("{}#{}", name, count), (index, node))
+        })
+        .collect()
+}
+
+/// Returns the order of the nodes which are also in the other graph.
+fn order_common<'a>(
+    nodes: &'a BTreeMap<String, (usize, &Code)>,
+    other: &BTreeMap<String, (usize, &Code)>,
+) -> BTreeMap<&'a str, usize> {
+    let mut common: Vec<_> = nodes
+        .iter()
+        .filter(|(k, _)| other.contains_key(*k))
+        .map(|(k, (index, _))| (*index, k.as_str()))
+        .collect();
+    common.sort_unstable();
+    common
+        .into_iter()
+        .enumerate()
+        .map(|(order, (_, k))| (k, order))
+        .collect()
+}
+
+fn fmt_value(value: &Option<ast::Value>) -> String {
+    match value {
+        Some(value) => format!("{:?}", value),
+        None => "(required)".to_string(),
+    }
+}
+
+fn fmt_out(out: &ast::Out) -> String {
+    format!("{:?}", out)
+}
+
+fn fmt_shape(shape: &Option<Shape>) -> String {
+    match shape {
+        Some(shape) => {
+            let dims: Vec<_> = shape
+                .iter()
+                .map(|dim| match dim {
+                    Some(dim) => dim.to_string(),
+                    None => "?".to_string(),
+                })
+                .collect();
+            format!("[{}]", dims.join(", "))
+        }
+        None => "(dynamic)".to_string(),
+    }
+}
+
+fn fmt_code_shapes(shapes: &CodeShapes) -> String {
+    let fmt_shapes = |shapes: &Shapes| {
+        shapes
+            .iter()
+            .map(|(name, shape)| format!("{}={}", name, fmt_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "{} -> {}",
+        fmt_shapes(&shapes.input),
+        fmt_shapes(&shapes.output)
+    )
+}
+
+fn fmt_code(code: &Code) -> String {
+    match code {
+        Code::Node(code) => code.data.name.clone(),
+        Code::Extern(code) => format!("{} ({})", &code.data.name, fmt_ty(code.ty)),
+    }
+}
+
+fn fmt_ty(ty: ast::ExternNodeType) -> String {
+    format!("{:?}", ty).trim_end().to_string()
+}
@@ -1,4 +1,8 @@
+use std::fmt;
+
 use bincode::ErrorKind as BincodeError;
+use serde_json::Error as JsonError;
+use serde_yaml::Error as YamlError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -6,15 +10,86 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     IOError(std::io::Error),
     BincodeError(BincodeError),
+    JsonError(JsonError),
+    YamlError(YamlError),
+    InvalidHeader,
+    NotProgram,
+    UnsupportedFormatVersion { given: u16, supported: u16 },
+    UnsupportedFlags { flags: u32 },
+    MismatchedChecksum { expected: u64, given: u64 },
+    InvalidGraphId { id: u64 },
+    MissingScriptSource { name: String },
 }
 
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
-        // note: only test the types
-        matches!(
-            (self, other),
-            (Self::IOError(_), Self::IOError(_)) | (Self::BincodeError(_), Self::BincodeError(_)),
-        )
+        match (self, other) {
+            // note: only test the types of the external errors
+            (Self::IOError(_), Self::IOError(_))
+            | (Self::BincodeError(_), Self::BincodeError(_)) => true,
+            (Self::InvalidHeader, Self::InvalidHeader) | (Self::NotProgram, Self::NotProgram) => {
+                true
+            }
+            (
+                Self::UnsupportedFormatVersion { given, supported },
+                Self::UnsupportedFormatVersion {
+                    given: given_other,
+                    supported: supported_other,
+                },
+            ) => given == given_other && supported == supported_other,
+            (Self::UnsupportedFlags { flags }, Self::UnsupportedFlags { flags: flags_other }) => {
+                flags == flags_other
+            }
+            (
+                Self::MismatchedChecksum { expected, given },
+                Self::MismatchedChecksum {
+                    expected: expected_other,
+                    given: given_other,
+                },
+            ) => expected == expected_other && given == given_other,
+            (Self::InvalidGraphId { id }, Self::InvalidGraphId { id: id_other }) => id == id_other,
+            (
+                Self::MissingScriptSource { name },
+                Self::MissingScriptSource { name: name_other },
+            ) => name == name_other,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IOError(e) => write!(f, "io error: {}", e),
+            Self::BincodeError(e) => write!(f, "bincode error: {}", e),
+            Self::JsonError(e) => write!(f, "json error: {}", e),
+            Self::YamlError(e) => write!(f, "yaml error: {}", e),
+            Self::InvalidHeader => write!(f, "the header of the program is broken"),
+            Self::NotProgram => write!(f, "not an n3 program"),
+            Self::UnsupportedFormatVersion { given, supported } => write!(
+                f,
+                "the program has the format version {}, but only up to {} is supported; please update n3",
+                given, supported
+            ),
+            Self::UnsupportedFlags { flags } => {
+                write!(f, "the program has unsupported flags: {:#x}", flags)
+            }
+            Self::MismatchedChecksum { expected, given } => write!(
+                f,
+                "the program is corrupted: expected checksum {:016x}, but given {:016x}",
+                expected, given
+            ),
+            Self::InvalidGraphId { id } => write!(
+                f,
+                "the graph id {} should be the index of the graph (starting from 1)",
+                id
+            ),
+            Self::MissingScriptSource { name } => write!(
+                f,
+                "the program is corrupted: the source of the script `{}` is missing",
+                name
+            ),
+        }
     }
 }
 
@@ -38,3 +113,15 @@ impl From<BincodeError> for Error {
         Self::BincodeError(error)
     }
 }
+
+impl From<JsonError> for Error {
+    fn from(error: JsonError) -> Self {
+        Self::JsonError(error)
+    }
+}
+
+impl From<YamlError> for Error {
+    fn from(error: YamlError) -> Self {
+        Self::YamlError(error)
+    }
+}
@@ -1,9 +1,15 @@
+pub const CACHE_DIR: &str = "cache";
 pub const DATA_DIR: &str = "data";
 pub const LOGS_DIR: &str = "logs";
 pub const MODELS_DIR: &str = "models";
 pub const NODES_DIR: &str = "nodes";
 pub const NODES_USER_DIR: &str = "__user__";
+pub const PACKAGES_DIR: &str = "packages";
+
+pub const CHECKPOINT_EXT: &str = "pth";
 
 pub const N3_SOURCE_ROOT: &str = "source_root";
 pub const N3_ROOT: &str = "root";
+pub const N3_PATH: &str = "path";
+pub const N3_REGISTRY: &str = "registry";
 pub const N3_MACHINES: &str = "machines";
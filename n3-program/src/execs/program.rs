@@ -1,6 +1,7 @@
+use std::collections::BTreeMap;
 use std::io;
 
-use crate::code::Codes;
+use crate::code::{CodeShapes, Codes};
 use crate::compact::Program as CompactedProgram;
 use crate::error::Result;
 use crate::externs::PythonScripts;
@@ -14,6 +15,8 @@ pub struct Program {
     pub graph: Variables,
     pub nodes: Codes,
     pub scripts: PythonScripts,
+    /// The resolved shapes of the nodes, keyed by the same names.
+    pub shapes: BTreeMap<String, CodeShapes>,
 }
 
 impl PartialEq for Program {
@@ -22,6 +25,7 @@ impl PartialEq for Program {
         self.graph.eq(&other.graph)
             && self.nodes.eq(&other.nodes)
             && self.scripts.eq(&other.scripts)
+            && self.shapes.eq(&other.shapes)
     }
 }
 
@@ -37,12 +41,48 @@ impl Program {
         self.compact().save(writer)
     }
 
+    pub fn save_with_flags<W>(&self, writer: W, flags: u32) -> Result<()>
+    where
+        W: io::Write,
+    {
+        self.compact().save_with_flags(writer, flags)
+    }
+
     pub fn save_to_binary(&self) -> Result<Vec<u8>> {
         let mut binary = vec![];
         self.save(&mut binary)?;
         Ok(binary)
     }
 
+    pub fn save_to_binary_with_flags(&self, flags: u32) -> Result<Vec<u8>> {
+        let mut binary = vec![];
+        self.save_with_flags(&mut binary, flags)?;
+        Ok(binary)
+    }
+
+    /// Returns the content hash of the program, which is the same for the same model and args.
+    ///
+    /// Note that the env is not hashed.
+    pub fn digest(&self) -> Result<String> {
+        self.compact().digest()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        self.compact().to_json()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        Ok(CompactedProgram::from_json(text)?.decompact())
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        self.compact().to_yaml()
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        Ok(CompactedProgram::from_yaml(text)?.decompact())
+    }
+
     pub fn load<R>(reader: R) -> Result<Self>
     where
         R: io::Read,
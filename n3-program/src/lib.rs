@@ -2,6 +2,7 @@ pub extern crate n3_parser_ast as ast;
 
 pub mod code;
 pub mod compact;
+pub mod diff;
 pub mod error;
 pub mod execs;
 pub mod externs;
@@ -10,6 +11,7 @@ pub mod nodes;
 pub mod variable;
 
 pub use self::code::{Code, CodeData};
+pub use self::diff::{Change, ChangeKind};
 pub use self::execs::{dirs, Program, PROGRAM_MAIN};
 pub use self::externs::{ExternCode, PythonScripts};
 pub use self::graph::ToValues;
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use pyo3::prelude::*;
 use pyo3::types::{IntoPyDict, PyList};
 
@@ -12,10 +14,22 @@ impl<'a> BuildCode<'a> for n3_program::NodeCode {
 
     fn build(&'a self, py: Python<'a>, args: Self::Args) -> PyResult<Self::Output> {
         // Step 1. Build the tensor graph
+        let mut shared = HashMap::new();
         let tensor_graph: Vec<_> = self
             .tensor_graph
             .iter()
-            .map(|x| x.build(py, &args))
+            .map(|x| match &x.data().shared {
+                // reuse the parameters of the shared node
+                Some(tag) => match shared.get(tag) {
+                    Some(&node) => NodeBuilder::share(x.data(), node, py),
+                    None => {
+                        let node = x.build(py, args)?;
+                        shared.insert(tag, node);
+                        Ok(node)
+                    }
+                },
+                None => x.build(py, args),
+            })
             .collect::<PyResult<_>>()?;
 
         // Step 2. Instantiate
@@ -39,6 +53,20 @@ impl<'a, 'b> NodeBuilder<'a, 'b>
 where
     'a: 'b,
 {
+    /// Instantiates a node with its own IO, holding the same modules as `origin`.
+    pub fn share(data: &'a CodeData, origin: &'a PyAny, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let tensor_graph: Vec<_> = origin
+            .getattr("_tensor_graph")?
+            .iter()?
+            .collect::<PyResult<_>>()?;
+
+        NodeBuilder {
+            data,
+            tensor_graph: &tensor_graph,
+        }
+        .build(py)
+    }
+
     pub fn build(self, py: Python<'a>) -> PyResult<&'a PyAny> {
         // Step 1. Build the data
         let name = self.data.name.as_str().into_py(py);
@@ -1,13 +1,16 @@
 use std::mem::ManuallyDrop;
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
 use n3_machine::PORT;
 use n3_machine_ffi::Program;
+use n3_program::dirs::N3_ROOT;
 
 use crate::args::parse_python_path;
 use crate::code::BuildCode;
 use crate::handler::PyHandler;
+use crate::publish::n3_publish;
 
 pub fn n3_execute(py: Python, program: &Program, handler: PyHandler) -> PyResult<()> {
     let is_root = program.id.primary == 0;
@@ -19,11 +22,17 @@ pub fn n3_execute(py: Python, program: &Program, handler: PyHandler) -> PyResult
     let mut text = n3_program::Program::load(&*program.text).unwrap();
 
     // Step 2. Attach variables
+    let digest = text.digest().unwrap();
     if text.env.is_none() {
         text.env = Some(Default::default());
     }
     let env = text.env.as_mut().unwrap();
     env.insert("id".to_string(), Some(program.id.local.into()));
+    env.insert(
+        "work id".to_string(),
+        Some(program.id.work.to_string().into()),
+    );
+    env.insert("digest".to_string(), Some(digest.into()));
     env.insert(
         "machine".to_string(),
         Some(machine_token.next().unwrap().to_string().into()),
@@ -34,6 +43,19 @@ pub fn n3_execute(py: Python, program: &Program, handler: PyHandler) -> PyResult
 
     let device_id = machine_token.next().unwrap_or("0").to_string();
 
+    // note: the checkpoint is given by the command line, e.g. `--from <work id>`
+    let checkpoint = env
+        .get("checkpoint")
+        .and_then(|x| x.as_ref())
+        .and_then(|x| x.unwrap_string())
+        .map(|x| x.to_string());
+    let root = env
+        .get(N3_ROOT)
+        .and_then(|x| x.as_ref())
+        .and_then(|x| x.unwrap_string())
+        .map(|x| x.to_string());
+    let shapes = std::mem::take(&mut text.shapes);
+
     // Step 3. Ready for DDP
     {
         let env = py.import("os")?.get("environ")?;
@@ -54,8 +76,20 @@ pub fn n3_execute(py: Python, program: &Program, handler: PyHandler) -> PyResult
     // Step 4. Define the node in REPL
     let text = text.build(py, ())?.into_py(py);
 
-    // Step 5. Do its own work
-    text.call_method1(py, &program.command, (handler,))?;
+    // Step 5. Load the trained weights
+    if let Some(checkpoint) = checkpoint {
+        text.call_method1(py, "load_checkpoint", (checkpoint,))?;
+    }
+
+    // Step 6. Do its own work
+    if program.command == "publish" {
+        let root = root.ok_or_else(|| {
+            PyValueError::new_err(format!("the root directory is not given: {:?}", N3_ROOT))
+        })?;
+        n3_publish(py, text.as_ref(py), &shapes, &root)?;
+    } else {
+        text.call_method1(py, &program.command, (handler,))?;
+    }
     Ok(())
 }
 
@@ -1,6 +1,6 @@
 use pyo3::prelude::*;
 
-use n3_machine_ffi::WorkHandler;
+use n3_machine_ffi::{Metrics, WorkHandler};
 
 #[pyclass]
 pub struct PyHandler {
@@ -25,6 +25,10 @@ impl PyHandler {
         self.inner.update_time(total_secs).unwrap()
     }
 
+    pub fn update_metrics(&self, metrics: Metrics) {
+        self.inner.update_metrics(&metrics).unwrap()
+    }
+
     pub fn end_ok(&self) {
         self.inner.end_ok().unwrap()
     }
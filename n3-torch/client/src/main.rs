@@ -2,6 +2,7 @@ mod args;
 mod code;
 mod exec;
 mod handler;
+mod publish;
 
 use n3_machine_ffi::{Program, WorkHandler};
 
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use n3_program::code::CodeShapes;
+use n3_program::dirs::MODELS_DIR;
+
+/// The name of the node which is published.
+pub const MODEL: &str = "model";
+
+pub fn n3_publish(
+    py: Python,
+    exec: &PyAny,
+    shapes: &BTreeMap<String, CodeShapes>,
+    root: &str,
+) -> PyResult<()> {
+    // Step 1. Get the resolved shapes
+    let shapes = shapes.get(MODEL).ok_or_else(|| {
+        PyValueError::new_err(format!("the exec has no node to publish: {:?}", MODEL))
+    })?;
+    for (name, shape) in &shapes.input {
+        // note: the batch dimension is given by the exporter
+        if !matches!(shape, Some(shape) if shape.iter().all(Option::is_some)) {
+            return Err(PyValueError::new_err(format!(
+                "the input shape is not resolved: {:?}",
+                name
+            )));
+        }
+    }
+
+    // Step 2. Get the model
+    let model = exec.getattr(MODEL)?;
+    let name: String = model.call_method0("get_name")?.extract()?;
+    let path: PathBuf = [root, MODELS_DIR, &name].iter().collect();
+
+    // Step 3. Trace and export the model
+    py.import("n3.util.publish")?.call_method1(
+        "publish",
+        (
+            model,
+            shapes.input.clone(),
+            shapes.output.clone(),
+            path.display().to_string(),
+        ),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use n3_builder::{dirs, ExecRoot, GlobalVars};
+
+    use super::*;
+    use crate::code::BuildCode;
+
+    #[test]
+    fn test_publish_lenet5() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        // note: the test is skipped without PyTorch
+        if py.import("torch").is_err() {
+            eprintln!("skipping the test: PyTorch is not installed");
+            return;
+        }
+        py.import("sys")
+            .and_then(|x| x.getattr("path"))
+            .and_then(|x| x.call_method1("insert", (0, "../ffi/python")))
+            .unwrap();
+
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "../../n3-builder/tests/data/")
+            .unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../ffi/python/n3").unwrap();
+        let mut root = ExecRoot::try_new(envs, Default::default()).unwrap();
+        root.set_cache_dir(std::env::temp_dir().join("n3-test-cache"));
+
+        let args = root.get("DummyImageClassification").unwrap();
+        args.set("data", "Mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+
+        let mut program = args.build_uncompacted().unwrap();
+        let shapes = std::mem::take(&mut program.shapes);
+        let exec = program.build(py, ()).unwrap();
+
+        // publish on CPU
+        let dir = std::env::temp_dir().join("n3-test-publish");
+        n3_publish(py, exec, &shapes, dir.to_str().unwrap()).unwrap();
+
+        let path = dir.join(MODELS_DIR).join("LeNet5");
+        for file in &["model.pt", "model.onnx", "metadata.json"] {
+            assert!(path.join(file).exists(), "missing {:?}", file);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    NoSuchScript { name: String },
+    ConflictingScripts { name: String },
+    UnsupportedImport { name: String, line: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchScript { name } => write!(f, "no such extern script: `{}`", name),
+            Self::ConflictingScripts { name } => write!(
+                f,
+                "the extern class `{}` is defined by the different scripts",
+                name
+            ),
+            Self::UnsupportedImport { name, line } => write!(
+                f,
+                "the extern class `{}` depends on n3, which cannot be exported: `{}`",
+                name, line
+            ),
+        }
+    }
+}
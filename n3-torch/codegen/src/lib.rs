@@ -0,0 +1,110 @@
+mod error;
+mod module;
+mod value;
+
+pub use self::error::{Error, Result};
+
+use n3_program::Program;
+
+/// Generates a standalone PyTorch module from the program.
+///
+/// Each node code is defined as a `nn.Module`, and the extern sources are inlined.
+pub fn export(program: &Program) -> Result<String> {
+    self::module::Module::build(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use n3_builder::{dirs, ExecRoot, GlobalVars};
+    use n3_program::ast;
+
+    use super::*;
+    use crate::value::ToPython;
+
+    const SNAPSHOTS_DIR: &str = "tests/data";
+
+    fn build_program() -> Program {
+        let envs = GlobalVars::default();
+        envs.set(dirs::N3_ROOT, "../../n3-builder/tests/data/")
+            .unwrap();
+        envs.set(dirs::N3_SOURCE_ROOT, "../ffi/python/n3").unwrap();
+        let mut root = ExecRoot::try_new(envs, Default::default()).unwrap();
+        root.set_cache_dir(std::env::temp_dir().join("n3-test-cache"));
+
+        let args = root.get("DummyImageClassification").unwrap();
+        args.set("data", "Mnist").unwrap();
+        args.set("model", "LeNet5").unwrap();
+        args.set("epoch", "1").unwrap();
+        args.set("batch size", "10").unwrap();
+        args.build_uncompacted().unwrap()
+    }
+
+    /// Compares with the snapshot, or updates it if `N3_UPDATE_SNAPSHOTS` is given.
+    fn assert_snapshot(name: &str, given: &str) {
+        let path = Path::new(SNAPSHOTS_DIR).join(name);
+        if std::env::var_os("N3_UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, given).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), given);
+    }
+
+    #[test]
+    fn test_export_ic() {
+        let program = build_program();
+        let module = export(&program).unwrap();
+        assert_snapshot("dummy_image_classification.py", &module);
+
+        // the same program is exported to the same module
+        assert_eq!(export(&build_program()).unwrap(), module);
+    }
+
+    #[test]
+    fn test_export_unsupported_import() {
+        let mut program = build_program();
+
+        let script = program.scripts.get_mut("Relu").unwrap();
+        script.source = format!("from n3.util import Out\n{}", &script.source);
+        assert_eq!(
+            export(&program),
+            Err(Error::UnsupportedImport {
+                name: "Relu".to_string(),
+                line: "from n3.util import Out".to_string(),
+            }),
+        );
+
+        program.scripts.remove("Relu");
+        assert_eq!(
+            export(&program),
+            Err(Error::NoSuchScript {
+                name: "Relu".to_string(),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_python_literals() {
+        let value = ast::Value::Map(
+            vec![
+                ("a".to_string(), Some(ast::Value::Real(1.0))),
+                (
+                    "b".to_string(),
+                    Some(ast::Value::String("it's".to_string())),
+                ),
+                ("c".to_string(), None),
+                (
+                    "d".to_string(),
+                    Some(ast::Value::List(vec![true.into(), 3u64.into()])),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            value.to_python(),
+            "{'a': 1.0, 'b': 'it\\'s', 'c': None, 'd': [True, 3]}"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use inflector::Inflector;
+use n3_program::externs::PythonScript;
+use n3_program::{ast, Code, ExternCode, NodeCode, Program, PythonScripts, ToValues, PROGRAM_MAIN};
+
+use crate::error::{Error, Result};
+use crate::value::{quote, ToPython};
+
+/// The definitions which replace the n3 package.
+const PRELUDE: &str = r#"class ExternNode(nn.Module):
+    # the standalone replacement of `n3.ExternNode`
+    def __init__(self, args: Any = None, input: Any = None, output: Any = None,
+                 values: Dict[str, Any] = {}) -> None:
+        super().__init__()
+        for k, v in values.items():
+            setattr(self, k.replace(' ', '_'), v)
+
+
+def _outputs(x: Any) -> Dict[str, torch.Tensor]:
+    # the externs may return a tensor, instead of the named outputs
+    return x if isinstance(x, dict) else {'x': x}"#;
+
+/// The imports which are used by the prelude.
+const PRELUDE_IMPORTS: &[&str] = &[
+    "from typing import Any, Dict",
+    "import torch",
+    "import torch.nn as nn",
+];
+
+/// The import of the extern base class, which is replaced by the prelude.
+const IMPORT_EXTERN_NODE: &str = "from n3 import ExternNode";
+
+pub struct Module<'a> {
+    scripts: &'a PythonScripts,
+    // the imports of the extern sources are gathered at the top
+    imports: BTreeSet<String>,
+    // class name -> source
+    externs: BTreeMap<String, String>,
+    // (class name, body), in the order of definition
+    classes: Vec<(String, String)>,
+}
+
+impl<'a> Module<'a> {
+    pub fn build(program: &'a Program) -> Result<String> {
+        let mut module = Self {
+            scripts: &program.scripts,
+            imports: PRELUDE_IMPORTS.iter().map(|x| x.to_string()).collect(),
+            externs: BTreeMap::new(),
+            classes: vec![],
+        };
+
+        // note: the data and optimizer nodes cannot be used without n3
+        let mut nodes = vec![];
+        for (name, code) in &program.nodes {
+            let value = match code {
+                Code::Node(node) => format!("{}()", module.add_node(node)?),
+                Code::Extern(node) if node.ty == ast::ExternNodeType::Default => {
+                    module.instantiate_extern(node, 8)?
+                }
+                Code::Extern(_) => continue,
+            };
+            nodes.push((name, value));
+        }
+
+        let exec = program
+            .scripts
+            .get(PROGRAM_MAIN)
+            .map(|x| x.name.as_str())
+            .unwrap_or_default();
+        Ok(module.write(exec, &nodes))
+    }
+
+    fn write(self, exec: &str, nodes: &[(&String, String)]) -> String {
+        let imports: Vec<_> = self.imports.into_iter().collect();
+        let mut sections = vec![
+            format!(
+                "# Generated by n3 from the exec `{}`, which depends on PyTorch only.\n{}",
+                exec,
+                imports.join("\n"),
+            ),
+            PRELUDE.to_string(),
+        ];
+
+        // Step 1. define the externs
+        sections.extend(self.externs.into_values());
+
+        // Step 2. define the nodes
+        sections.extend(
+            self.classes
+                .into_iter()
+                .map(|(name, body)| format!("class {}(nn.Module):\n{}", name, body.trim_end())),
+        );
+
+        // Step 3. instantiate the nodes of the exec
+        let mut build = "def build_nodes() -> Dict[str, nn.Module]:\n    return {\n".to_string();
+        for (name, value) in nodes {
+            build.push_str(&format!("        {}: {},\n", quote(name), value));
+        }
+        build.push_str("    }");
+        sections.push(build);
+
+        let mut module = sections.join("\n\n\n");
+        module.push('\n');
+        module
+    }
+
+    fn add_node(&mut self, node: &NodeCode) -> Result<String> {
+        let mut init = String::new();
+        let mut forward = String::new();
+
+        // Step 1. get the inputs
+        let inputs: Vec<_> = node.data.input.keys().map(|x| x.as_str()).collect();
+        forward.push_str(&format!(
+            "    def forward(self{}):\n",
+            inputs
+                .iter()
+                .map(|x| format!(", {}", x))
+                .collect::<String>()
+        ));
+        for name in &inputs {
+            let out = ast::Out::new(1, name.to_string());
+            forward.push_str(&format!("        {} = {}\n", var(&out), name));
+        }
+
+        // Step 2. wire the tensor graph
+        let mut shared: BTreeMap<&str, String> = BTreeMap::new();
+        for (index, code) in node.tensor_graph.iter().enumerate() {
+            let data = code.data();
+
+            // the shared nodes reuse the parameters of the first one
+            let attr = match data.shared.as_deref().and_then(|tag| shared.get(tag)) {
+                Some(attr) => attr.clone(),
+                None => {
+                    let attr = format!("{}_{}", data.name.to_snake_case(), index);
+                    let value = match code {
+                        Code::Node(code) => format!("{}()", self.add_node(code)?),
+                        Code::Extern(code) => self.instantiate_extern(code, 8)?,
+                    };
+                    init.push_str(&format!("        self.{} = {}\n", &attr, value));
+
+                    if let Some(tag) = data.shared.as_deref() {
+                        shared.insert(tag, attr.clone());
+                    }
+                    attr
+                }
+            };
+
+            let args: Vec<_> = data
+                .input
+                .iter()
+                .map(|(name, out)| format!("{}={}", name, var(out)))
+                .collect();
+            let call = format!("self.{}({})", &attr, args.join(", "));
+            let call = match code {
+                Code::Node(_) => call,
+                Code::Extern(_) => format!("_outputs({})", call),
+            };
+
+            if data.output.len() == 1 {
+                let (name, out) = data.output.iter().next().unwrap();
+                forward.push_str(&format!(
+                    "        {} = {}[{}]\n",
+                    var(out),
+                    call,
+                    quote(name)
+                ));
+            } else {
+                forward.push_str(&format!("        outputs = {}\n", call));
+                for (name, out) in &data.output {
+                    forward.push_str(&format!(
+                        "        {} = outputs[{}]\n",
+                        var(out),
+                        quote(name)
+                    ));
+                }
+            }
+        }
+
+        // Step 3. return the outputs of the last node
+        let outputs: Vec<_> = node
+            .tensor_graph
+            .last()
+            .into_iter()
+            .flat_map(|x| x.data().output.values())
+            .map(|out| format!("{}: {}", quote(&out.name), var(out)))
+            .collect();
+        forward.push_str(&format!("        return {{{}}}\n", outputs.join(", ")));
+
+        let body = format!(
+            "    def __init__(self) -> None:\n        super().__init__()\n{}\n{}",
+            init, forward
+        );
+        Ok(self.add_class(&node.data.name, body))
+    }
+
+    /// Defines the class, or reuses the same one.
+    fn add_class(&mut self, base: &str, body: String) -> String {
+        let mut name = base.to_string();
+        let mut count = 0;
+        loop {
+            match self.classes.iter().find(|(x, _)| *x == name) {
+                Some((_, x)) if *x == body => return name,
+                Some(_) => {
+                    count += 1;
+                    name = format!("{}_{}", base, count);
+                }
+                None => {
+                    self.classes.push((name.clone(), body));
+                    return name;
+                }
+            }
+        }
+    }
+
+    /// Returns the expression which instantiates the extern node.
+    fn instantiate_extern(&mut self, node: &ExternCode, indent: usize) -> Result<String> {
+        let script = self
+            .scripts
+            .get(&node.data.name)
+            .ok_or_else(|| Error::NoSuchScript {
+                name: node.data.name.clone(),
+            })?;
+        self.add_extern(script)?;
+
+        let values = node.data.graph.to_values();
+        if values.is_empty() {
+            return Ok(format!("{}(values={{}})", &script.name));
+        }
+
+        let indent = " ".repeat(indent);
+        let mut value = format!("{}(values={{\n", &script.name);
+        for (name, v) in &values {
+            value.push_str(&format!(
+                "{}    {}: {},\n",
+                &indent,
+                quote(name),
+                v.to_python()
+            ));
+        }
+        value.push_str(&format!("{}}})", &indent));
+        Ok(value)
+    }
+
+    fn add_extern(&mut self, script: &PythonScript) -> Result<()> {
+        let mut imports = vec![];
+        let mut source = String::new();
+        for line in script.source.lines() {
+            let trimmed = line.trim();
+            if trimmed == IMPORT_EXTERN_NODE {
+                continue;
+            }
+            if trimmed.starts_with("from n3") || trimmed.starts_with("import n3") {
+                return Err(Error::UnsupportedImport {
+                    name: script.name.clone(),
+                    line: trimmed.to_string(),
+                });
+            }
+            // note: only the top-level imports are gathered
+            if line.starts_with("import ") || line.starts_with("from ") {
+                imports.push(trimmed.to_string());
+                continue;
+            }
+            source.push_str(line.trim_end());
+            source.push('\n');
+        }
+        let source = source.trim().to_string();
+
+        match self.externs.get(&script.name) {
+            Some(x) if *x != source => Err(Error::ConflictingScripts {
+                name: script.name.clone(),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.imports.extend(imports);
+                self.externs.insert(script.name.clone(), source);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the name of the local variable which holds the out.
+fn var(out: &ast::Out) -> String {
+    match out.id {
+        Some(id) => format!("{}_{}", &out.name, id),
+        None => out.name.clone(),
+    }
+}
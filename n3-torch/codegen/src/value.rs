@@ -0,0 +1,68 @@
+use n3_program::{ast, BuildValue};
+
+/// Converts the values into the Python literals.
+pub trait ToPython {
+    fn to_python(&self) -> String;
+}
+
+impl ToPython for ast::Value {
+    fn to_python(&self) -> String {
+        match self.build() {
+            Self::Bool(true) => "True".to_string(),
+            Self::Bool(false) => "False".to_string(),
+            Self::UInt(v) => v.to_string(),
+            Self::Int(v) => v.to_string(),
+            Self::Real(v) if v.is_nan() => "float('nan')".to_string(),
+            Self::Real(v) if v.is_infinite() && v > 0.0 => "float('inf')".to_string(),
+            Self::Real(v) if v.is_infinite() => "float('-inf')".to_string(),
+            // note: the debug format always has the fractional part, e.g. `1.0`
+            Self::Real(v) => format!("{:?}", v),
+            Self::String(v) => quote(&v),
+            Self::List(v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|x| x.to_python())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Map(v) => format!(
+                "{{{}}}",
+                v.iter()
+                    .map(|(k, v)| format!("{}: {}", quote(k), v.to_python()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            // the unresolved values are given as None, like the torch client
+            _ => "None".to_string(),
+        }
+    }
+}
+
+impl<T> ToPython for Option<T>
+where
+    T: ToPython,
+{
+    fn to_python(&self) -> String {
+        match self {
+            Some(value) => value.to_python(),
+            None => "None".to_string(),
+        }
+    }
+}
+
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
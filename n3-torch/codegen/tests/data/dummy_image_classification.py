@@ -0,0 +1,193 @@
+# Generated by n3 from the exec `DummyImageClassification`, which depends on PyTorch only.
+from typing import Any, Dict
+import torch
+import torch.nn as nn
+
+
+class ExternNode(nn.Module):
+    # the standalone replacement of `n3.ExternNode`
+    def __init__(self, args: Any = None, input: Any = None, output: Any = None,
+                 values: Dict[str, Any] = {}) -> None:
+        super().__init__()
+        for k, v in values.items():
+            setattr(self, k.replace(' ', '_'), v)
+
+
+def _outputs(x: Any) -> Dict[str, torch.Tensor]:
+    # the externs may return a tensor, instead of the named outputs
+    return x if isinstance(x, dict) else {'x': x}
+
+
+class AssertShape(ExternNode):
+    def forward(self, **kwargs):
+        return kwargs
+
+
+class Conv2D(ExternNode):
+    kernel_size: int
+    padding: int
+
+    stride: int
+
+    input_channels: int
+    output_channels: int
+
+    bias: bool
+
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
+        self._inner = nn.Conv2d(
+            in_channels=self.input_channels,
+            out_channels=self.output_channels,
+            kernel_size=self.kernel_size,
+            stride=self.stride,
+            padding=self.padding,
+            bias=self.bias,
+        )
+
+    def forward(self, x):
+        return self._inner(x)
+
+
+class CrossEntropy(ExternNode):
+    number_of_classes: int
+
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
+        self._inner = nn.CrossEntropyLoss()
+
+    def forward(self, x, y):
+        return self._inner(x, y)
+
+
+class Dropout(ExternNode):
+    probability: float
+
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
+        self._inner = nn.Dropout(self.probability)
+
+    def forward(self, x):
+        return self._inner(x)
+
+
+class Linear(ExternNode):
+    input_channels: int
+    output_channels: int
+
+    bias: bool
+
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
+        self._inner = nn.Linear(self.input_channels,
+                                self.output_channels,
+                                self.bias)
+
+    def forward(self, x):
+        return self._inner(x)
+
+
+class Relu(ExternNode):
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
+        self._inner = nn.ReLU()
+
+    def forward(self, x):
+        return self._inner(x)
+
+
+class ToLinear(ExternNode):
+    def forward(self, x):
+        return x.reshape(x.size(0), -1)
+
+
+class MyConv(nn.Module):
+    def __init__(self) -> None:
+        super().__init__()
+        self.conv_2d_0 = Conv2D(values={
+            '_margin': 0,
+            'bias': True,
+            'height': 28,
+            'input channels': 1,
+            'kernel size': 5,
+            'output channels': 32,
+            'padding': 2,
+            'stride': 2,
+            'width': 28,
+        })
+        self.relu_1 = Relu(values={})
+
+    def forward(self, x):
+        x_1 = x
+        x_2 = _outputs(self.conv_2d_0(x=x_1))['x']
+        x_3 = _outputs(self.relu_1(x=x_2))['x']
+        return {'x': x_3}
+
+
+class MyConv_1(nn.Module):
+    def __init__(self) -> None:
+        super().__init__()
+        self.conv_2d_0 = Conv2D(values={
+            '_margin': 0,
+            'bias': True,
+            'height': 14,
+            'input channels': 32,
+            'kernel size': 5,
+            'output channels': 64,
+            'padding': 2,
+            'stride': 2,
+            'width': 14,
+        })
+        self.relu_1 = Relu(values={})
+
+    def forward(self, x):
+        x_1 = x
+        x_2 = _outputs(self.conv_2d_0(x=x_1))['x']
+        x_3 = _outputs(self.relu_1(x=x_2))['x']
+        return {'x': x_3}
+
+
+class LeNet5(nn.Module):
+    def __init__(self) -> None:
+        super().__init__()
+        self.assert_shape_0 = AssertShape(values={})
+        self.my_conv_1 = MyConv()
+        self.my_conv_2 = MyConv_1()
+        self.to_linear_3 = ToLinear(values={
+            'output shapes': {'x': [3136]},
+        })
+        self.linear_4 = Linear(values={
+            'bias': True,
+            'input channels': 3136,
+            'output channels': 1024,
+        })
+        self.relu_5 = Relu(values={})
+        self.dropout_6 = Dropout(values={
+            'probability': 0.5,
+        })
+        self.linear_7 = Linear(values={
+            'bias': True,
+            'input channels': 1024,
+            'output channels': 10,
+        })
+
+    def forward(self, x):
+        x_1 = x
+        x_1 = _outputs(self.assert_shape_0(x=x_1))['x']
+        x_2 = self.my_conv_1(x=x_1)['x']
+        x_3 = self.my_conv_2(x=x_2)['x']
+        x_4 = _outputs(self.to_linear_3(x=x_3))['x']
+        x_5 = _outputs(self.linear_4(x=x_4))['x']
+        x_5 = _outputs(self.relu_5(x=x_5))['x']
+        x_5 = _outputs(self.dropout_6(x=x_5))['x']
+        x_6 = _outputs(self.linear_7(x=x_5))['x']
+        return {'x': x_6}
+
+
+def build_nodes() -> Dict[str, nn.Module]:
+    return {
+        'loss': CrossEntropy(values={
+            'number of classes': 10,
+        }),
+        'model': LeNet5(),
+    }
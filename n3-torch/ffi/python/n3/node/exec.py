@@ -2,7 +2,6 @@ import abc
 import os
 from typing import Any, Dict, List
 
-import inflection
 import torch
 import torch.nn as nn
 from torch import Tensor
@@ -11,6 +10,7 @@ from .data import DataNode
 from .node import Node, TensorDict
 from .optim import OptimNode
 from ..util.args import Args
+from ..util.dirs import CHECKPOINT_EXT, MODELS_DIR
 from ..writer import EpochWriter, ExecWriter
 
 Metrics = Dict[str, Any]
@@ -23,6 +23,8 @@ class ExecNode(metaclass=abc.ABCMeta):
     _is_root: bool
     _is_distributed: bool
 
+    _checkpoint_path: str
+
     _writer: ExecWriter
 
     def __init__(self, args: Args, nodes: Dict[str, Node] = {}) -> None:
@@ -40,6 +42,10 @@ class ExecNode(metaclass=abc.ABCMeta):
         self._is_root = env['is root']
         self._is_distributed = env['is distributed']
 
+        # the trained weights are keyed by the work id and the program digest
+        self._checkpoint_path = os.path.join(env['root'], MODELS_DIR, env['work id'],
+                                             f'{env["digest"]}.{CHECKPOINT_EXT}')
+
         # Distributed Training
         if self._is_distributed:
             torch.distributed.init_process_group(backend='nccl')
@@ -79,6 +85,9 @@ class ExecNode(metaclass=abc.ABCMeta):
             return [self.tensor_to(v) for v in value]
         return value.to(self._machine)
 
+    def load_checkpoint(self, path: str) -> None:
+        raise NotImplementedError
+
     def close(self):
         pass
 
@@ -137,6 +146,8 @@ class Trainer(ExecNode, metaclass=abc.ABCMeta):
         self.optimizer._initialize(self.model)
 
     def _train_end(self) -> None:
+        if self._is_root:
+            self.save_checkpoint()
         self.close()
 
     def _train_epoch_begin(self, writer: EpochWriter, metrics: Metrics) -> None:
@@ -146,6 +157,9 @@ class Trainer(ExecNode, metaclass=abc.ABCMeta):
         for name, value in metrics.items():
             writer.write(name, value, use_batch=True)
         writer.flush()
+        if self._is_root:
+            self._writer.update_rust_metrics(
+                {name: writer.mean(value) for name, value in metrics.items()})
 
     def _train_iter_begin(self, data: List[Tensor]) -> TensorDict:
         return {'x': data[0]}, {'y': data[1]}
@@ -155,47 +169,67 @@ class Trainer(ExecNode, metaclass=abc.ABCMeta):
             metrics['loss'] += loss.item()
             self._writer.update_rust_kwargs(metrics)
 
-    @abc.abstractmethod
     def eval(self, kwargs) -> None:
-        raise NotImplementedError
+        # Step 1. ready to evaluate
+        self._eval_begin(kwargs)
 
-    def publish(self, kwargs, args: Args) -> None:
-        # Step 1. ready to publish
-        self.model.eval()
-
-        # Step 2. get dummy input
-        x, _ = next(iter(self.data.get_train_dataset()))
+        # Step 2-1. peek the IO
+        dataset = self.data.get_eval_dataset()
+        metrics = {
+            'loss': 0.0,
+        }
+        self._eval_epoch_begin(metrics)
 
-        # Step 3. get parameters
-        input_names = ['x']  # 모델의 입력값을 가리키는 이름
-        output_names = ['out_x']  # 모델의 출력값을 가리키는 이름
-        dynamic_axes = {'x': {0: 'batch_size'},  # 가변적인 길이를 가진 차원
-                        'out_x': {0: 'batch_size'}}
+        with torch.no_grad():
+            for data in dataset:
+                data = self._train_iter_begin(data)
+                x, y = self.tensor_to(data)
+                # Step 2-2. predict classses
+                y_pred = self.model(**x)
+                # Step 2-3. calculate difference (loss)
+                loss = self.loss(**y_pred, **y)['x']
+                # Step 2-4. store result
+                self._eval_iter_end(metrics, x, y, y_pred, loss)
+                if not self._writer.is_running():
+                    break
 
-        export_params = True  # 모델 파일 안에 학습된 모델 가중치를 저장할지의 여부
-        opset_version = 10  # 모델을 변환할 때 사용할 ONNX 버전
-        do_constant_folding = True  # 최적화시 상수폴딩을 사용할지의 여부
+        # Step 2-5. store the averaged metrics
+        if self._is_root:
+            num_batch = max(len(dataset), 1)
+            self._writer.update_rust_metrics(
+                {name: value / num_batch for name, value in metrics.items()})
 
-        name = inflection.underscore(self.model.get_name())
-        output_path = os.path.join(args.output_path, f'{name}.onnx')
+        # Step 3. clean up
+        self.close()
 
-        # Step 4. export to onnx
-        torch.onnx.export(self.model, {'x': x}, output_path,
-                          input_names=input_names,
-                          output_names=output_names,
-                          dynamic_axes=dynamic_axes,
+    def _eval_begin(self, kwargs) -> None:
+        self._writer.attach_rust_kwargs(kwargs)
+        for name, node in self.nodes().items():
+            setattr(self, name, self.to(node))
 
-                          export_params=export_params,
-                          opset_version=opset_version,
-                          do_constant_folding=do_constant_folding,
-                          )
+    def _eval_epoch_begin(self, metrics: Metrics) -> None:
+        self.model.eval()
 
-        # Step 5. do target-specific publishing
-        # TODO: to be implemented
+    def _eval_iter_end(self, metrics: Metrics, x: Tensor, y: Tensor, y_pred: Tensor, loss: Tensor) -> None:
+        if self._is_root:
+            metrics['loss'] += loss.item()
 
     def get_model_name(self) -> str:
         return self.model.get_name()
 
+    def save_checkpoint(self) -> None:
+        # should be called on root
+        model = self.model
+        if isinstance(model, nn.parallel.DistributedDataParallel):
+            model = model.module
+
+        os.makedirs(os.path.dirname(self._checkpoint_path), exist_ok=True)
+        torch.save({'model': model.state_dict()}, self._checkpoint_path)
+
+    def load_checkpoint(self, path: str) -> None:
+        state = torch.load(path, map_location='cpu')
+        self.model.load_state_dict(state['model'])
+
     def close(self):
         super().close()
         self._writer.close()
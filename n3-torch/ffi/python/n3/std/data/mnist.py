@@ -8,6 +8,8 @@ from n3 import DataNode
 class Mnist(DataNode):
     _train_dataset = None
     _train_loader = None
+    _eval_dataset = None
+    _eval_loader = None
 
     def get_train_dataset(self):
         if self._train_dataset is None:
@@ -25,4 +27,13 @@ class Mnist(DataNode):
         raise NotImplementedError
 
     def get_eval_dataset(self):
-        raise NotImplementedError
+        if self._eval_dataset is None:
+            self._eval_dataset = MNIST(self.dataset_dir,
+                                       transform=transforms.ToTensor(),
+                                       train=False,
+                                       download=True)
+            self._eval_loader = DataLoader(dataset=self._eval_dataset,
+                                           batch_size=self.batch_size,
+                                           num_workers=4,
+                                           shuffle=False)
+        return self._eval_loader
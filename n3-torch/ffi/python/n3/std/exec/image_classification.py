@@ -8,12 +8,19 @@ class ImageClassification(Trainer):
 
     def _train_iter_end(self, metrics, x, y, y_pred, loss):
         super()._train_iter_end(metrics, x, y, y_pred, loss)
+        metrics['accuracy'] += _accuracy(y, y_pred)
+
+    def _eval_epoch_begin(self, metrics):
+        super()._eval_epoch_begin(metrics)
+        metrics['accuracy'] = 0.0
+
+    def _eval_iter_end(self, metrics, x, y, y_pred, loss):
+        super()._eval_iter_end(metrics, x, y, y_pred, loss)
+        metrics['accuracy'] += _accuracy(y, y_pred)
 
-        y = y['y']
-        y_pred = y_pred['x']
-        accuracy = (y == y_pred.max(dim=-1)[1]).sum().item()
-        accuracy /= y.size(0)
-        metrics['accuracy'] += accuracy
 
-    def eval(self, handler, writer):
-        raise NotImplementedError
+def _accuracy(y, y_pred):
+    y = y['y']
+    y_pred = y_pred['x']
+    accuracy = (y == y_pred.max(dim=-1)[1]).sum().item()
+    return accuracy / y.size(0)
@@ -3,3 +3,5 @@ LOGS_DIR = 'logs'
 MODELS_DIR = 'models'
 NODES_DIR = 'nodes'
 NODES_USER_DIR = '__user__'
+
+CHECKPOINT_EXT = 'pth'
@@ -0,0 +1,85 @@
+import json
+import os
+from typing import Dict, List, Optional, Tuple
+
+import torch
+import torch.nn as nn
+
+from ..node.node import NodeExecutable
+
+Shape = Optional[List[Optional[int]]]
+Shapes = Dict[str, Shape]
+
+METADATA_FILE = 'metadata.json'
+ONNX_FILE = 'model.onnx'
+TORCHSCRIPT_FILE = 'model.pt'
+
+OPSET_VERSION = 11  # 모델을 변환할 때 사용할 ONNX 버전
+
+
+class _Positional(nn.Module):
+    # the exporters trace the positional tensors only
+    def __init__(self, node: NodeExecutable, input_names: List[str], output_names: List[str]) -> None:
+        super().__init__()
+        self.node = node
+        self._input_names = input_names
+        self._output_names = output_names
+
+    def forward(self, *args: torch.Tensor) -> Tuple[torch.Tensor, ...]:
+        output = self.node(**dict(zip(self._input_names, args)))
+        return tuple(output[name] for name in self._output_names)
+
+
+def _dummy_input(shape: Shape) -> torch.Tensor:
+    # the batch dimension is not given by the program
+    return torch.zeros(1, *shape)
+
+
+def _metadata(names: List[str], tensors: Tuple[torch.Tensor, ...]) -> List[Dict]:
+    # the batch dimension is variable
+    return [{'name': name, 'shape': [None, *x.shape[1:]]}
+            for name, x in zip(names, tensors)]
+
+
+def publish(node: NodeExecutable, input: Shapes, output: Shapes, path: str) -> None:
+    # Step 1. get dummy input
+    input_names = list(input)
+    output_names = list(output)
+    dummy_input = tuple(_dummy_input(input[name]) for name in input_names)
+
+    model = _Positional(node, input_names, output_names).cpu().eval()
+    os.makedirs(path, exist_ok=True)
+
+    with torch.no_grad():
+        dummy_output = model(*dummy_input)
+
+        # Step 2. export to torchscript
+        traced = torch.jit.trace(model, dummy_input)
+        traced.save(os.path.join(path, TORCHSCRIPT_FILE))
+
+        # Step 3. export to onnx
+        # note: the input and output names should be distinct
+        onnx_output_names = [f'out_{name}' for name in output_names]
+        dynamic_axes = {name: {0: 'batch_size'}
+                        for name in input_names + onnx_output_names}
+        torch.onnx.export(model, dummy_input, os.path.join(path, ONNX_FILE),
+                          input_names=input_names,
+                          output_names=onnx_output_names,
+                          dynamic_axes=dynamic_axes,
+                          export_params=True,
+                          opset_version=OPSET_VERSION,
+                          do_constant_folding=True,
+                          )
+
+    # Step 4. write the metadata
+    metadata = {
+        'name': node.get_name(),
+        'input': _metadata(input_names, dummy_input),
+        'output': _metadata(output_names, dummy_output),
+        'torchscript': TORCHSCRIPT_FILE,
+        'onnx': ONNX_FILE,
+        # note: the onnx outputs are renamed, to be distinct from the inputs
+        'onnx_output': onnx_output_names,
+    }
+    with open(os.path.join(path, METADATA_FILE), 'w') as f:
+        json.dump(metadata, f, indent=4)
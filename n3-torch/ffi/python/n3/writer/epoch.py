@@ -36,6 +36,9 @@ class Epoch:
         else:
             raise Exception(f'not supported type: {type(value)}')
 
+    def mean(self, value):
+        return value / self._num_batch
+
     def _write_scalar(self, name, value):
         self._writer.add_scalar(self._tag(name), value, self._value)
 
@@ -49,6 +49,12 @@ class ExecWriter:
         if time_total_secs:
             self._rust_kwargs.update_time(time_total_secs)
 
+    def update_rust_metrics(self, metrics):
+        # should be called on root
+        assert self._rust_kwargs is not None
+        self._rust_kwargs.update_metrics(
+            {name: float(value) for name, value in metrics.items()})
+
     def close(self):
         self._epoch_writer = None
 
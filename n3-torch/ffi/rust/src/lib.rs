@@ -3,6 +3,8 @@ use std::ops::{Deref, DerefMut};
 use n3_machine_ffi::{Program, Query, Result, WorkHandler};
 
 pub trait PyMachine {
+    fn query(&self) -> &Query;
+
     fn is_running(&self) -> bool;
 
     fn py_spawn(&mut self, program: &mut Program, handler: &WorkHandler) -> Result<()>;
@@ -21,8 +23,12 @@ pub trait ProcessMachine<P>: PyMachine {
 impl<T, P> PyMachine for T
 where
     T: ProcessMachine<P> + Deref<Target = P> + DerefMut,
-    P: PyMachine,
+    P: PyMachine + 'static,
 {
+    fn query(&self) -> &Query {
+        self.deref().query()
+    }
+
     fn is_running(&self) -> bool {
         self.deref().is_running()
     }
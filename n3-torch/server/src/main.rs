@@ -67,6 +67,7 @@ mod tests {
             .unwrap();
         envs.set(dirs::N3_SOURCE_ROOT, "../ffi/python/n3").unwrap();
         let mut root = ExecRoot::try_new(envs, Default::default()).unwrap();
+        root.set_cache_dir(std::env::temp_dir().join("n3-test-cache"));
 
         let args = root.get("DummyImageClassification").unwrap();
         args.set("data", "Mnist").unwrap();
@@ -36,6 +36,10 @@ impl ProcessMachine {
 }
 
 impl PyMachine for ProcessMachine {
+    fn query(&self) -> &Query {
+        &self.query
+    }
+
     fn is_running(&self) -> bool {
         self.process.is_some()
     }
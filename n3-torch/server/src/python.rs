@@ -1,4 +1,4 @@
-use n3_machine_ffi::{Machine, Program, SignalHandler, WorkHandler, WorkStatus};
+use n3_machine_ffi::{Machine, Program, Query, SignalHandler, WorkHandler, WorkStatus};
 use n3_torch_ffi::PyMachine;
 
 pub struct PyMachineBase<T>
@@ -29,6 +29,10 @@ impl<T> Machine for PyMachineBase<T>
 where
     T: PyMachine,
 {
+    fn query(&self) -> &Query {
+        self.inner.query()
+    }
+
     fn spawn(&mut self, program: &mut Program, handler: &SignalHandler) -> WorkStatus {
         if self.handler.is_some() {
             return self.status();
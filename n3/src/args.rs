@@ -1,7 +1,93 @@
-use n3_builder::{Args, GlobalVars};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, ArgMatches};
+
+use n3_builder::{ast, dirs, Args, GlobalVars, Result, Vars, QUERY_SPLIT_1};
 
 pub struct Command<'a> {
     pub command: &'a str,
     pub env: &'a GlobalVars,
     pub args: Option<Args<'a>>,
 }
+
+/// An owned description of a CLI arg, which is derived from a variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArgSpec {
+    /// The name in snake case, which is also the long flag.
+    pub name: String,
+    pub short: Option<char>,
+    pub help: Option<String>,
+    pub required: bool,
+    pub multiple: bool,
+    pub choices: Option<Vec<String>>,
+}
+
+impl ArgSpec {
+    /// Describes the variables, except the hidden ones.
+    pub fn from_vars(vars: &Vars) -> Vec<Self> {
+        vars.to_variables()
+            .inner
+            .iter()
+            .map(|(name, var)| (name, var.borrow()))
+            .filter(|(_, var)| var.name != dirs::N3_SOURCE_ROOT)
+            .map(|(name, var)| {
+                // note: the choices are shown as the possible values
+                let help = match (&var.description, &var.constraint) {
+                    (_, Some(ast::LetConstraint::Choices(_))) | (_, None) => {
+                        var.description.clone()
+                    }
+                    (Some(desc), Some(range)) => Some(format!("{} (in {:?})", desc, range)),
+                    (None, Some(range)) => Some(format!("(in {:?})", range)),
+                };
+
+                Self {
+                    name: name.clone(),
+                    short: var
+                        .shortcut
+                        .as_ref()
+                        .and_then(|x| x.to_lowercase().chars().next()),
+                    help,
+                    required: var.value.is_none(),
+                    multiple: matches!(var.ty, Some(ast::LetType::List(_))),
+                    choices: var
+                        .constraint
+                        .as_ref()
+                        .and_then(|x| x.choices())
+                        .map(|x| x.to_vec()),
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_arg(&self) -> Arg {
+        let mut arg = Arg::new(self.name.clone())
+            .long(self.name.clone())
+            .required(self.required);
+        if let Some(short) = self.short {
+            arg = arg.short(short);
+        }
+        if let Some(help) = &self.help {
+            arg = arg.help(help.clone());
+        }
+        if self.multiple {
+            arg = arg.num_args(1..).action(ArgAction::Append);
+        }
+        if let Some(choices) = &self.choices {
+            arg = arg
+                .value_parser(PossibleValuesParser::new(choices.clone()))
+                .ignore_case(true);
+        }
+        arg
+    }
+
+    /// Applies the given values into the variables.
+    pub fn apply(specs: &[Self], matches: &ArgMatches, vars: &Vars) -> Result<()> {
+        let vars = vars.to_variables();
+        for spec in specs {
+            if let Some(values) = matches.get_many::<String>(&spec.name) {
+                let value = values.map(|x| x.as_str()).collect::<Vec<_>>();
+                vars.set(&spec.name, &value.join(QUERY_SPLIT_1))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::fs;
+use std::io::{self, Write};
+
+use clap::ArgMatches;
+
+use n3_builder::inflector::Inflector;
+use n3_builder::Result;
+
+use crate::Command;
+
+pub const EMIT_BINARY: &str = "binary";
+pub const EMIT_JSON: &str = "json";
+pub const EMIT_YAML: &str = "yaml";
+
+pub fn f(command: Command, exec: &str, matches: &ArgMatches) -> Result<()> {
+    // Step 1. build a program
+    let program = command.args.unwrap().build_uncompacted_with_env()?;
+
+    // Step 2. emit
+    let emit = matches
+        .get_one::<String>("emit")
+        .map(|x| x.as_str())
+        .unwrap_or(EMIT_BINARY);
+    let output = match emit {
+        EMIT_JSON => program.to_json()?.into_bytes(),
+        EMIT_YAML => program.to_yaml()?.into_bytes(),
+        _ => program.save_to_binary()?,
+    };
+
+    // Step 3. write out (default: the binary to a file, and the texts to stdout)
+    match matches.get_one::<String>("output") {
+        Some(path) => fs::write(path, output)?,
+        None if emit == EMIT_BINARY => fs::write(format!("{}.n3b", exec.to_snake_case()), output)?,
+        None => io::stdout().write_all(&output)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::{ExecRoot, GlobalVars, Result};
+
+pub const DENY_WARNINGS: &str = "warnings";
+
+pub fn command() -> Command {
+    Command::new("check")
+        .about("Checks the nodes and reports the suspicious parts of them")
+        .arg(
+            Arg::new("nodes")
+                .help("The nodes to check (default: all)")
+                .num_args(1..),
+        )
+        .arg(
+            Arg::new("deny")
+                .long("deny")
+                .help("Treats the given lints as errors")
+                .value_parser([DENY_WARNINGS]),
+        )
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let root = ExecRoot::try_new(env.clone(), Default::default())?;
+
+    let names: Vec<_> = match matches.get_many::<String>("nodes") {
+        Some(names) => names.cloned().collect(),
+        None => root.node_names().into_iter().collect(),
+    };
+
+    let mut num_warnings = 0;
+    let mut num_errors = 0;
+
+    for warning in root.lint_roots() {
+        println!("warning: {}", warning);
+        num_warnings += 1;
+    }
+    for name in &names {
+        let result = root.lint(name).and_then(|warnings| {
+            for warning in &warnings {
+                println!("warning: {}", warning);
+            }
+            num_warnings += warnings.len();
+            root.check(name)
+        });
+        if let Err(e) = result {
+            println!("error: failed to build `{}`: {}", name, e);
+            num_errors += 1;
+        }
+    }
+
+    let deny_warnings =
+        matches.get_one::<String>("deny").map(|x| x.as_str()) == Some(DENY_WARNINGS);
+    println!(
+        "checked {} node(s): {} warning(s), {} error(s)",
+        names.len(),
+        num_warnings,
+        num_errors,
+    );
+
+    if num_errors > 0 || (deny_warnings && num_warnings > 0) {
+        process::exit(1);
+    }
+    Ok(())
+}
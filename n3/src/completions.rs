@@ -0,0 +1,195 @@
+use std::io::{self, Write};
+
+use clap::{Arg, ArgMatches, Command};
+use clap_complete::Shell;
+
+use n3_builder::inflector::Inflector;
+use n3_builder::{ast, ExecRoot, ExecRootConfig, GlobalVars, Result};
+
+use crate::args::ArgSpec;
+use crate::SWITCH_FN_2;
+
+/// The commands which take an exec as the next word.
+const EXEC_COMMANDS: &[&str] = &["sweep"];
+
+pub fn command() -> Command {
+    Command::new("completions")
+        .about("Generates the shell completion script")
+        .arg(
+            Arg::new("shell")
+                .help("The shell to complete")
+                .value_parser(["bash", "zsh", "fish"])
+                .required(true),
+        )
+}
+
+pub fn command_complete() -> Command {
+    Command::new("complete")
+        .about("Completes the command line, called by the shells")
+        .hide(true)
+        .arg(
+            Arg::new("words")
+                .num_args(0..)
+                .allow_hyphen_values(true)
+                .trailing_var_arg(true),
+        )
+}
+
+pub fn f(env_specs: &[ArgSpec], matches: &ArgMatches) -> Result<()> {
+    let shell = matches.get_one::<String>("shell").unwrap();
+
+    let mut script = vec![];
+    clap_complete::generate(
+        shell.parse::<Shell>().unwrap(),
+        &mut crate::cli(env_specs, None),
+        "n3",
+        &mut script,
+    );
+    let script = String::from_utf8_lossy(&script);
+
+    // note: the args of the execs and the node names are completed by `n3 complete`
+    let script = match shell.as_str() {
+        "bash" => format!("{}{}", script, BASH_DYNAMIC),
+        // note: the zsh script should end with calling the completion function
+        "zsh" => {
+            let script = script.trim_end();
+            let script = script.strip_suffix(ZSH_TAIL).unwrap_or(script);
+            format!("{}\n{}", script.trim_end(), ZSH_DYNAMIC)
+        }
+        "fish" => format!("{}{}", script, FISH_DYNAMIC),
+        _ => unreachable!("The shell should be one of the possible values."),
+    };
+    io::stdout().write_all(script.as_bytes())?;
+    Ok(())
+}
+
+/// Prints the candidates of the last word, one per line.
+///
+/// The words are the command line without `n3`, and the last one is the word being completed.
+/// Note that the errors are not printed, so that the shells can fall back to the static completion.
+pub fn complete(env: &GlobalVars, env_specs: &[ArgSpec], matches: &ArgMatches) -> Result<()> {
+    let words: Vec<_> = matches
+        .get_many::<String>("words")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect();
+
+    for candidate in candidates(env, env_specs, &words).unwrap_or_default() {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+fn candidates(env: &GlobalVars, env_specs: &[ArgSpec], words: &[String]) -> Option<Vec<String>> {
+    let (current, words) = words.split_last()?;
+
+    // Step 1. complete the command
+    let command = match words.first() {
+        Some(command) => command.as_str(),
+        None => {
+            let cli = crate::cli(env_specs, None);
+            let commands = cli.get_subcommands().filter(|x| !x.is_hide_set());
+            return Some(commands.map(|x| x.get_name().to_string()).collect());
+        }
+    };
+    if !SWITCH_FN_2.contains(&command) && !EXEC_COMMANDS.contains(&command) {
+        return None;
+    }
+
+    // note: the root dir should not be created while completing
+    let config = ExecRootConfig {
+        create_root_dir: Some(false),
+    };
+    let mut root = ExecRoot::try_new(env.clone(), config).ok()?;
+
+    // Step 2. complete the exec
+    let exec = match words.get(1) {
+        Some(exec) => exec.to_pascal_case(),
+        None => {
+            let execs = root.exec_names().into_iter();
+            return Some(execs.map(|x| x.to_snake_case()).collect());
+        }
+    };
+    let args = root.get(&exec).ok()?;
+    let exec_specs = ArgSpec::from_vars(&args);
+
+    // Step 3. complete the names of the args
+    if current.starts_with('-') {
+        let cli = crate::cli(env_specs, Some((command, &exec_specs)));
+        let flags = cli
+            .find_subcommand(command)?
+            .get_arguments()
+            .filter_map(|x| x.get_long())
+            .map(|x| format!("--{}", x))
+            .chain(Some("--help".to_string()));
+        return Some(flags.collect());
+    }
+
+    // Step 4. complete the value of the arg
+    let prev = words.last()?;
+    let args = args.to_variables();
+    let var = match prev.strip_prefix("--") {
+        Some(name) => args.inner.get(name)?,
+        None => {
+            let shortcut = prev.strip_prefix('-')?;
+            args.inner.values().find(|var| {
+                let var = var.borrow();
+                var.shortcut.as_deref().map(|x| x.to_lowercase()) == Some(shortcut.to_string())
+            })?
+        }
+    };
+    let var = var.borrow();
+
+    if let Some(choices) = var.constraint.as_ref().and_then(|x| x.choices()) {
+        return Some(choices.to_vec());
+    }
+    match &var.ty {
+        Some(ast::LetType::Node(Some(ty))) => Some(root.node_names_by_type(*ty)),
+        Some(ast::LetType::Bool) => Some(vec!["yes".to_string(), "no".to_string()]),
+        _ => None,
+    }
+}
+
+const BASH_DYNAMIC: &str = r#"
+_n3_dynamic() {
+    local values
+    values="$(n3 complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null)"
+    if [[ -n "${values}" ]]; then
+        COMPREPLY=( $(compgen -W "${values}" -- "${COMP_WORDS[COMP_CWORD]}") )
+    else
+        _n3 "$@"
+    fi
+}
+
+complete -F _n3_dynamic -o bashdefault -o default n3
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_n3_dynamic() {
+    local -a values
+    values=(${(f)"$(n3 complete -- ${words[2,CURRENT]} 2>/dev/null)"})
+    if (( ${#values} )); then
+        compadd -a values
+    else
+        _n3 "$@"
+    fi
+}
+
+if [ "$funcstack[1]" = "_n3" ]; then
+    _n3_dynamic "$@"
+else
+    compdef _n3_dynamic n3
+fi
+"#;
+
+/// The end of the zsh script by `clap_complete`, which is replaced with `ZSH_DYNAMIC`.
+const ZSH_TAIL: &str = r#"if [ "$funcstack[1]" = "_n3" ]; then
+    _n3 "$@"
+else
+    compdef _n3 n3
+fi"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c n3 -f -a '(n3 complete -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)'
+"#;
@@ -0,0 +1,37 @@
+use std::fs::File;
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::{Program, Result};
+
+pub fn command() -> Command {
+    Command::new("diff")
+        .about("Compares two built programs")
+        .arg(
+            Arg::new("old")
+                .help("The path of the old program")
+                .required(true),
+        )
+        .arg(
+            Arg::new("new")
+                .help("The path of the new program")
+                .required(true),
+        )
+}
+
+pub fn f(matches: &ArgMatches) -> Result<()> {
+    let old = Program::load(File::open(matches.get_one::<String>("old").unwrap())?)?;
+    let new = Program::load(File::open(matches.get_one::<String>("new").unwrap())?)?;
+
+    let changes = old.diff(&new);
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    // like diff(1), exit with 1 if the programs differ
+    if !changes.is_empty() {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -1,22 +1,52 @@
+use std::fs;
 use std::thread;
 use std::time::Duration;
 
 use chrono::prelude::*;
+use clap::ArgMatches;
 use indicatif::{ProgressBar, ProgressStyle};
 
+use n3_builder::inflector::Inflector;
 use n3_builder::{dirs, Result};
 use n3_net_client::Work;
 
 use crate::Command;
 
-pub fn execute(command: Command) -> Result<()> {
+pub fn execute(command: Command, exec: &str, matches: &ArgMatches) -> Result<()> {
     let machines = command.env.get_string_list(dirs::N3_MACHINES)?;
 
     // Step 1. build a program
-    let program = command.args.unwrap().build_with_env()?;
+    let args = command.args.unwrap();
+    let config = crate::effective_config(command.env, &args);
+    let mut program = args.build_uncompacted_with_env()?;
+    let digest = program.digest()?;
+    println!("program: {}", &digest);
+
+    // Step 1-1. attach the checkpoint to load
+    if let Some(from) = matches.get_one::<String>("from") {
+        let checkpoint = command.env.find_checkpoint(from, &digest)?;
+        println!("checkpoint: {}", checkpoint.display());
+
+        let env = program.env.get_or_insert_with(Default::default);
+        env.insert(
+            "checkpoint".to_string(),
+            Some(checkpoint.display().to_string().into()),
+        );
+    }
+    let program = program.save_to_binary()?;
+
+    // Step 1-2. write the effective config next to the logs
+    let logs_dir = command
+        .env
+        .root_dir()
+        .join(dirs::LOGS_DIR)
+        .join(exec.to_snake_case());
+    fs::create_dir_all(&logs_dir)?;
+    fs::write(logs_dir.join(format!("{}.toml", digest)), config.to_toml()?)?;
 
     // Step 2. spawn a work
     let work = Work::spawn(&program, command.command, &machines).unwrap();
+    println!("work: {}", work.id());
 
     // Step 3. wait the work
     let pb = ProgressBar::new(1000000); // 100.0000%
@@ -26,7 +56,7 @@ pub fn execute(command: Command) -> Result<()> {
             .progress_chars("#>-"),
     );
 
-    'pb: loop {
+    let status = 'pb: loop {
         for i in 0..10 {
             thread::sleep(Duration::from_millis(100));
 
@@ -41,16 +71,23 @@ pub fn execute(command: Command) -> Result<()> {
                 }
 
                 if !status.is_running {
-                    break 'pb;
+                    break 'pb status;
                 }
             } else {
                 pb.inc(0);
             }
         }
-    }
+    };
 
+    pb.finish_and_clear();
     drop(work);
 
     // Step 4. finalize
+    if let Some(msg) = &status.error_msg {
+        println!("error: {}", msg);
+    }
+    for (name, value) in &status.metrics {
+        println!("{}: {}", name, value);
+    }
     Ok(())
 }
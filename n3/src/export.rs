@@ -0,0 +1,37 @@
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+use clap::ArgMatches;
+
+use n3_builder::Result;
+
+use crate::Command;
+
+pub const LANG_PYTORCH: &str = "pytorch";
+
+pub fn f(command: Command, exec: &str, matches: &ArgMatches) -> Result<()> {
+    // Step 1. build a program
+    let program = command.args.unwrap().build_uncompacted()?;
+
+    // Step 2. generate the source code
+    let lang = matches.get_one::<String>("lang").unwrap();
+    let output = match lang.as_str() {
+        LANG_PYTORCH => n3_torch_codegen::export(&program),
+        _ => unreachable!("unknown language: {}", lang),
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            println!("error: failed to export `{}`: {}", exec, e);
+            process::exit(1);
+        }
+    };
+
+    // Step 3. write out (default: stdout)
+    match matches.get_one::<String>("output") {
+        Some(path) => fs::write(path, output)?,
+        None => io::stdout().write_all(output.as_bytes())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::{ast, ExecRoot, GlobalVars, Result};
+
+pub fn command() -> Command {
+    Command::new("info")
+        .about("Shows the details of a node")
+        .arg(Arg::new("node").help("The node to show").required(true))
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let name = matches.get_one::<String>("node").unwrap();
+
+    let root = ExecRoot::try_new(env.clone(), Default::default())?;
+    let node = root.parse_node(name)?.node;
+
+    println!("name: {}", &node.name);
+    println!("type: {}", crate::list::type_name(node.ty));
+    if let Some(path) = root.node_origins().get(name) {
+        println!("path: {}", path);
+    }
+
+    if !node.graph.is_empty() {
+        println!("\nvariables:");
+        for var in node.graph.values() {
+            print!("    {:?}", var);
+        }
+    }
+
+    // note: the first node of the graph is the input, and the last one with shapes is the output
+    let input = node.tensor_graph.values().next();
+    let output = node
+        .tensor_graph
+        .values()
+        .rev()
+        .find(|x| x.shapes.is_some());
+    if let Some(shapes) = input.and_then(|x| x.shapes.as_ref()) {
+        println!("\ninput: {}", fmt_shapes(shapes));
+    }
+    if let Some(shapes) = output.and_then(|x| x.shapes.as_ref()) {
+        println!("output: {}", fmt_shapes(shapes));
+    }
+
+    if !node.children.is_empty() {
+        println!("\nchildren:");
+        for child in node.children.values() {
+            println!("    {}", &child.name);
+        }
+    }
+
+    if !node.withs.is_empty() {
+        println!("\nwiths:");
+        for with in node.withs.values() {
+            let values: Vec<_> = with
+                .graph
+                .iter()
+                .map(|(name, value)| format!("{} = {:?}", name, value))
+                .collect();
+            println!("    {}: {}", &with.name, values.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn fmt_shapes(shapes: &ast::Shapes) -> String {
+    let shapes = shapes.0.borrow();
+    let fmt_shape = |shape: &Option<ast::Shape>| match shape {
+        Some(shape) => {
+            let dims: Vec<_> = shape.0.iter().map(|x| format!("{:?}", x)).collect();
+            format!("[{}]", dims.join(", "))
+        }
+        None => "*".to_string(),
+    };
+
+    // note: a single tensor is named `x`, and is shown without its name
+    match shapes.get("x") {
+        Some(shape) if shapes.len() == 1 => fmt_shape(shape),
+        _ => shapes
+            .iter()
+            .map(|(name, shape)| format!("{} = {}", name, fmt_shape(shape)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
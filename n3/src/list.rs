@@ -0,0 +1,67 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use n3_builder::{ast, ExecRoot, GlobalVars, Result};
+
+/// The filters of the nodes, with the types they accept.
+const FILTERS: &[(&str, &str, ast::NodeType)] = &[
+    ("execs", "Lists the exec nodes", ast::NodeType::Exec),
+    ("models", "Lists the default nodes", ast::NodeType::Default),
+    (
+        "data",
+        "Lists the data nodes",
+        ast::NodeType::Extern(ast::ExternNodeType::Data),
+    ),
+    (
+        "optim",
+        "Lists the optimizer nodes",
+        ast::NodeType::Extern(ast::ExternNodeType::Optim),
+    ),
+    (
+        "externs",
+        "Lists the extern nodes",
+        ast::NodeType::Extern(ast::ExternNodeType::Default),
+    ),
+];
+
+pub fn command() -> Command {
+    FILTERS.iter().fold(
+        Command::new("list").about("Lists the available nodes (default: all, with their types)"),
+        |command, (name, help, _)| {
+            command.arg(
+                Arg::new(*name)
+                    .long(*name)
+                    .help(*help)
+                    .action(ArgAction::SetTrue),
+            )
+        },
+    )
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let root = ExecRoot::try_new(env.clone(), Default::default())?;
+
+    let filters: Vec<_> = FILTERS
+        .iter()
+        .filter(|(name, _, _)| matches.get_flag(name))
+        .map(|(_, _, ty)| *ty)
+        .collect();
+
+    for (name, ty) in root.node_types() {
+        if filters.is_empty() {
+            println!("{}\t{}", name, type_name(ty));
+        } else if filters.contains(&ty) {
+            println!("{}", name);
+        }
+    }
+    Ok(())
+}
+
+pub fn type_name(ty: ast::NodeType) -> &'static str {
+    match ty {
+        ast::NodeType::Default => "node",
+        ast::NodeType::Exec => "exec",
+        ast::NodeType::Extern(ast::ExternNodeType::Default) => "extern",
+        ast::NodeType::Extern(ast::ExternNodeType::Data) => "data",
+        ast::NodeType::Extern(ast::ExternNodeType::Optim) => "optim",
+    }
+}
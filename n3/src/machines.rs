@@ -0,0 +1,42 @@
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::Result;
+
+pub fn command() -> Command {
+    Command::new("machines")
+        .about("Lists the machines which the hosts would load by the queries")
+        .arg(
+            Arg::new("queries")
+                .help("The queries of the machines (ex. cpu, cuda, cuda:0, 10.0.0.2:cuda:0)")
+                .num_args(1..)
+                .required(true),
+        )
+}
+
+pub fn f(matches: &ArgMatches) -> Result<()> {
+    let mut has_errors = false;
+    for query in matches.get_many::<String>("queries").unwrap() {
+        let machines = match n3_net_client::machines(query) {
+            Ok(machines) => machines,
+            Err(e) => {
+                eprintln!("error: failed to query `{}`: {}", query, e);
+                has_errors = true;
+                continue;
+            }
+        };
+        if machines.is_empty() {
+            eprintln!("error: no such machine `{}`", query);
+            has_errors = true;
+        }
+        for machine in machines {
+            println!("{}\t{}", query, machine);
+        }
+    }
+
+    if has_errors {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -1,171 +1,285 @@
 mod args;
+mod build;
+mod check;
+mod completions;
+mod diff;
 mod exec;
+mod export;
+mod info;
+mod list;
+mod machines;
 mod monitor;
+mod pkg;
+mod sweep;
+mod which;
 
-use clap::{crate_authors, crate_version, App, AppSettings, Arg, ArgMatches, Result as ClapResult};
+use clap::{crate_authors, crate_version, Arg, ArgAction};
 use inflector::Inflector;
 
-use n3_builder::{ast, dirs, inflector, ExecRoot, GlobalVars, Result, Vars, QUERY_SPLIT_1};
+use std::path::Path;
 
-use crate::args::Command;
+use n3_builder::{dirs, inflector, Args, Config, ExecRoot, GlobalVars, Result};
+
+use crate::args::{ArgSpec, Command};
 
 pub const SWITCH_FN_1: &[(&str, FnExec)] = &[("monitor", self::monitor::f)];
-pub const SWITCH_FN_2: &[&str] = &["train", "eval", "publish"];
+pub const SWITCH_FN_2: &[&str] = &["build", "train", "eval", "publish", "export"];
 
 pub type FnExec = fn(Command) -> Result<()>;
 
 fn main() -> Result<()> {
     // Step 1. parse envs
     let env = GlobalVars::default();
-    let env_vars = env.to_variables();
-
-    // Step 2. parse command & exec
-    let cmd_args: Vec<_> = std::env::args().skip(1).collect();
-    let command = cmd_args.get(0);
-    let exec = cmd_args.get(1);
-
-    // Step 3-1. execute commands that don't need a root
-    if let Some((_, f)) = command
-        .map(|x| SWITCH_FN_1.iter().find(|(k, _)| k == x))
-        .flatten()
-    {
-        let args_set = [&env_vars];
-        match unsafe { parse_args(&args_set) } {
-            Ok(_) => {
-                f(Command {
-                    command: command.unwrap(),
+
+    // note: the config file should be applied before parsing, to fill the required args
+    let cmd_args: Vec<_> = std::env::args().collect();
+    let config = load_config(&cmd_args)?;
+    if let Some(config) = &config {
+        config.apply_env(&env)?;
+    }
+    let env_specs = ArgSpec::from_vars(&env);
+
+    // Step 2. parse the command, without the args of the exec
+    let matches = cli(&env_specs, None).get_matches_from(&cmd_args);
+    let (command, matches) = matches.subcommand().unwrap();
+
+    match command {
+        // Step 3-1. check the nodes
+        "check" => crate::check::f(&env, matches),
+        // Step 3-2. show where the nodes came from
+        "which" => crate::which::f(&env, matches),
+        // Step 3-3. manage the packages
+        "pkg" => crate::pkg::f(&env, matches),
+        // Step 3-4. list the nodes
+        "list" => crate::list::f(&env, matches),
+        // Step 3-5. show the details of a node
+        "info" => crate::info::f(&env, matches),
+        // Step 3-6. list the machines of the hosts
+        "machines" => crate::machines::f(matches),
+        // Step 3-7. compare the programs
+        "diff" => crate::diff::f(matches),
+        // Step 3-8. sweep the args
+        "sweep" => crate::sweep::f(&env, matches),
+        // Step 3-9. generate the shell completion script
+        "completions" => crate::completions::f(&env_specs, matches),
+        // Step 3-10. complete the command line, called by the shells
+        "complete" => crate::completions::complete(&env, &env_specs, matches),
+        _ => {
+            // Step 3-11. execute commands that don't need a root
+            if let Some((_, f)) = SWITCH_FN_1.iter().find(|(k, _)| *k == command) {
+                ArgSpec::apply(&env_specs, matches, &env)?;
+                return f(Command {
+                    command,
                     env: &env,
                     args: None,
-                })?;
-            }
-            Err(e) => {
-                println!("{}", e);
+                });
             }
-        }
 
-        // drop order: app (=matches) -> args
-        drop(env);
-        Ok(())
-    }
-    // Step 3-2. execute commands with a root
-    else if let Some(exec) = exec {
-        let mut root = ExecRoot::try_new(env.clone(), Default::default())?;
-        let args = root.get(&exec.to_pascal_case())?;
-
-        let args_set = [&env_vars, &args.to_variables()];
-        match unsafe { parse_args(&args_set) } {
-            Ok(_) => {
-                crate::exec::execute(Command {
-                    command: command.unwrap(),
-                    env: &env,
-                    args: Some(args),
-                })?;
+            // Step 3-12. execute commands with a root
+            let exec = match matches.get_one::<String>("exec") {
+                Some(exec) => exec,
+                None => {
+                    let mut cli = cli(&env_specs, Some((command, &[])));
+                    cli.build();
+                    cli.find_subcommand_mut(command).unwrap().print_help()?;
+                    return Ok(());
+                }
+            };
+
+            let mut root = ExecRoot::try_new(env.clone(), Default::default())?;
+            let mut args = root.get(&exec.to_pascal_case())?;
+            if let Some(config) = &config {
+                config.apply_args(&env, &mut args)?;
             }
-            Err(e) => {
-                println!("{}", e);
+
+            // Step 3-12-1. parse again, with the args of the exec
+            let exec_specs = ArgSpec::from_vars(&args);
+            let matches = cli(&env_specs, Some((command, &exec_specs))).get_matches_from(&cmd_args);
+            let matches = matches.subcommand_matches(command).unwrap();
+
+            ArgSpec::apply(&env_specs, matches, &env)?;
+            ArgSpec::apply(&exec_specs, matches, &args)?;
+            for spec in matches.get_many::<String>("set").into_iter().flatten() {
+                args.add_override(spec.parse()?);
             }
-        }
 
-        // drop order: app (=matches) -> args
-        drop(root);
-        drop(env);
-        Ok(())
-    }
-    // Step 3-3. show help message
-    else {
-        let app = unsafe { subcommand_args(&env_vars, app()) };
-        let matches = app.get_matches_from(&["--help"]);
-
-        // drop order: app (=matches) -> args
-        drop(matches);
-        drop(env);
-        Ok(())
-    }
-}
+            if matches.get_flag("dump_config") {
+                print!("{}", effective_config(&env, &args).to_toml()?);
+                return Ok(());
+            }
 
-unsafe fn parse_args<'a, 'b, 'c>(args: &[&'a Vars]) -> ClapResult<Result<ArgMatches<'b>>>
-where
-    'a: 'b,
-    'b: 'c,
-{
-    let mut app = app();
-    for args in args {
-        app = subcommand_args(args, app);
-    }
+            let command = Command {
+                command,
+                env: &env,
+                args: Some(args),
+            };
 
-    let matches = app.get_matches_safe()?;
-    for args in args {
-        if let Err(e) = apply(&matches, args) {
-            return Ok(Err(e));
+            match command.command {
+                "build" => crate::build::f(command, exec, matches),
+                "export" => crate::export::f(command, exec, matches),
+                _ => crate::exec::execute(command, exec, matches),
+            }
         }
     }
-    Ok(Ok(matches))
 }
 
-fn apply(matches: &ArgMatches, args: &Vars) -> Result<()> {
-    for name in args.inner.keys() {
-        if let Some(values) = matches.values_of(name) {
-            let value = values.collect::<Vec<_>>().join(QUERY_SPLIT_1);
-            args.set(name, &value)?;
+/// Loads the config file given by `--config`.
+fn load_config(cmd_args: &[String]) -> Result<Option<Config>> {
+    let path = cmd_args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--config" {
+            cmd_args.get(i + 1).map(|x| x.as_str())
+        } else {
+            arg.strip_prefix("--config=")
         }
-    }
-    Ok(())
+    });
+    path.map(|x| Config::load(Path::new(x))).transpose()
 }
 
-unsafe fn subcommand_args<'a, 'b, 'c>(args: &'a Vars, mut app: App<'b, 'c>) -> App<'b, 'c>
-where
-    'a: 'b,
-    'b: 'c,
-{
-    for (name, var) in &args.inner {
-        // drop order: app -> args
-        let var = var.try_borrow_unguarded().unwrap();
-
-        // hidden
-        if var.name == dirs::N3_SOURCE_ROOT {
-            continue;
-        }
+/// Collects the effective values of the env and the args, except the hidden ones.
+pub fn effective_config(env: &GlobalVars, args: &Args) -> Config {
+    let mut config = Config::from_vars(&[env, args]).with_overrides(args.overrides());
+    config.values.remove(dirs::N3_SOURCE_ROOT);
+    config
+}
 
-        let mut arg = Arg::with_name(name).long(name).takes_value(true);
-        if let Some(shortcut) = &var.shortcut {
-            arg = arg.short(shortcut.to_lowercase());
-        }
-        if let Some(desc) = &var.description {
-            arg = arg.help(desc);
-        }
-        if let Some(ast::LetType::List(_)) = var.ty {
-            arg = arg.multiple(true);
-        }
-        if var.value.is_none() {
-            arg = arg.required(true);
-        }
+/// Describes the whole command line.
+///
+/// The args of an exec are given only to its command, as they are known after loading the exec.
+/// Without them, the rest of the command line is accepted as is.
+fn cli(env_specs: &[ArgSpec], exec: Option<(&str, &[ArgSpec])>) -> clap::Command {
+    let mut cli = clap::Command::new("n3")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Neural Network Notation")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(crate::check::command())
+        .subcommand(crate::which::command())
+        .subcommand(crate::pkg::command())
+        .subcommand(crate::list::command())
+        .subcommand(crate::info::command())
+        .subcommand(crate::machines::command())
+        .subcommand(crate::diff::command())
+        .subcommand(crate::sweep::command())
+        .subcommand(crate::completions::command())
+        .subcommand(crate::completions::command_complete());
+
+    for (name, _) in SWITCH_FN_1 {
+        let command = clap::Command::new(*name).about(about(name));
+        cli = cli.subcommand(command.args(env_specs.iter().map(ArgSpec::to_arg)));
+    }
+
+    for name in SWITCH_FN_2 {
+        let mut command = clap::Command::new(*name)
+            .about(about(name))
+            .arg(Arg::new("exec").help("The exec to run").required(true));
+
+        match exec {
+            Some((exec_command, exec_specs)) if exec_command == *name => {
+                // note: the programs are not run by these commands, so the machines are not needed
+                let is_offline = *name == "build" || *name == "export";
+                let env_args = env_specs.iter().map(|spec| {
+                    let arg = spec.to_arg();
+                    if is_offline && spec.name == dirs::N3_MACHINES {
+                        arg.required(false)
+                    } else {
+                        arg
+                    }
+                });
 
-        app = app.arg(arg);
+                command = command
+                    .args(env_args)
+                    .args(exec_specs.iter().map(ArgSpec::to_arg))
+                    .args(exec_args(name));
+            }
+            // note: the help is shown after loading the exec, to describe its args
+            _ => {
+                command = command
+                    .mut_arg("exec", |x| {
+                        x.required(false).required_unless_present("help")
+                    })
+                    .disable_help_flag(true)
+                    .arg(
+                        Arg::new("help")
+                            .short('h')
+                            .long("help")
+                            .help("Print help")
+                            .action(ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("args")
+                            .help("The args of the exec")
+                            .num_args(0..)
+                            .allow_hyphen_values(true)
+                            .trailing_var_arg(true),
+                    );
+            }
+        }
+        cli = cli.subcommand(command);
     }
-    app
+    cli
 }
 
-fn app<'a, 'b, 'c>() -> App<'b, 'c>
-where
-    'a: 'b,
-    'b: 'c,
-{
-    let exec_commands: Vec<_> = SWITCH_FN_2.iter().map(|x| ("command", *x)).collect();
+/// Returns the args which are given to every exec.
+fn exec_args(command: &str) -> Vec<Arg> {
+    let mut args = vec![
+        Arg::new("config")
+            .long("config")
+            .help("The config file of the args (.toml or .yaml)"),
+        Arg::new("dump_config")
+            .long("dump-config")
+            .help("Prints the effective config of the args, instead of running")
+            .action(ArgAction::SetTrue),
+        Arg::new("set")
+            .long("set")
+            .help("Overrides the nested node variable, e.g. \"model.K=7\"")
+            .action(ArgAction::Append),
+    ];
+    if command != "build" && command != "export" {
+        args.push(
+            Arg::new("from")
+                .long("from")
+                .help("The checkpoint to load, by the work id or the path"),
+        );
+    }
+    if command == "build" {
+        args.push(
+            Arg::new("emit")
+                .long("emit")
+                .help("The format of the built program")
+                .value_parser([build::EMIT_BINARY, build::EMIT_JSON, build::EMIT_YAML]),
+        );
+        args.push(
+            Arg::new("output")
+                .long("output")
+                .help("The path to write the built program"),
+        );
+    }
+    if command == "export" {
+        args.push(
+            Arg::new("lang")
+                .long("lang")
+                .help("The language of the exported source")
+                .value_parser([export::LANG_PYTORCH])
+                .default_value(export::LANG_PYTORCH),
+        );
+        args.push(
+            Arg::new("output")
+                .long("output")
+                .help("The path to write the exported source"),
+        );
+    }
+    args
+}
 
-    App::new("n3")
-        .version(crate_version!())
-        .author(crate_authors!())
-        .about("Neural Network Notation")
-        .arg(
-            Arg::with_name("root_dir")
-                .long("root_dir")
-                .help("The n3 program's own local directory")
-                .takes_value(true),
-        )
-        .setting(AppSettings::ArgRequiredElseHelp)
-        .setting(AppSettings::ColoredHelp)
-        .setting(AppSettings::ColorAuto)
-        .setting(AppSettings::GlobalVersion)
-        .arg(Arg::with_name("command").required(true))
-        .arg(Arg::with_name("exec").required_ifs(&exec_commands))
+fn about(command: &str) -> &'static str {
+    match command {
+        "monitor" => "Shows the logs of the works with tensorboard",
+        "build" => "Builds an exec into a program",
+        "train" => "Trains an exec on the machines",
+        "eval" => "Evaluates an exec on the machines",
+        "publish" => "Publishes an exec on the machines",
+        "export" => "Exports an exec into a standalone source",
+        _ => unreachable!("unknown command: {}", command),
+    }
 }
@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::dirs::PACKAGES_DIR;
+use n3_builder::package::{
+    Lockfile, Manifest, PackageId, Packages, Registry, VersionReq, LOCK_FILE, MANIFEST_FILE,
+};
+use n3_builder::{GlobalVars, Result};
+
+pub fn command() -> Command {
+    Command::new("pkg")
+        .about("Manages the node packages of the local root")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("add")
+                .about("Adds the packages to the manifest, and installs them")
+                .arg(
+                    Arg::new("packages")
+                        .help("The packages to add (ex. author/name, author/name@^0.1)")
+                        .num_args(1..)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("install")
+                .about("Installs the packages in the manifest, following the lockfile"),
+        )
+        .subcommand(Command::new("list").about("Lists the installed packages"))
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let root_dir = env.root_dir();
+    let manifest_path = root_dir.join(MANIFEST_FILE);
+    let lock_path = root_dir.join(LOCK_FILE);
+
+    let registry = Registry::new(env.registry_dir());
+    let packages = Packages::new(root_dir.join(PACKAGES_DIR));
+
+    match matches.subcommand() {
+        Some(("add", matches)) => {
+            let mut manifest = Manifest::load_or_default(&manifest_path)?;
+            for package in matches.get_many::<String>("packages").unwrap() {
+                let (id, req) = parse_package(&registry, package)?;
+                manifest.dependencies.insert(id.to_string(), req);
+            }
+            manifest.save(&manifest_path)?;
+
+            install(&registry, &packages, &manifest, &lock_path)
+        }
+        Some(("install", _)) => {
+            let manifest = Manifest::load_or_default(&manifest_path)?;
+            install(&registry, &packages, &manifest, &lock_path)
+        }
+        Some(("list", _)) => {
+            for manifest in packages.installed()? {
+                let package = manifest.package.unwrap();
+                println!("{} {}", package.id()?, package.version);
+                for (module, req) in &manifest.python_dependencies {
+                    println!("    python: {}{}", module, req);
+                }
+            }
+            Ok(())
+        }
+        _ => unreachable!("the subcommand is required"),
+    }
+}
+
+/// Parses `author/name[@req]`, or requires the latest version compatible with it.
+fn parse_package(registry: &Registry, package: &str) -> Result<(PackageId, VersionReq)> {
+    let mut tokens = package.splitn(2, '@');
+    let id: PackageId = tokens.next().unwrap().parse()?;
+
+    let req = match tokens.next() {
+        Some(req) => VersionReq::parse(req)?,
+        None => {
+            let version = registry.resolve(&id, &VersionReq::STAR)?;
+            VersionReq::parse(&format!("^{}", version))?
+        }
+    };
+    Ok((id, req))
+}
+
+fn install(
+    registry: &Registry,
+    packages: &Packages,
+    manifest: &Manifest,
+    lock_path: &Path,
+) -> Result<()> {
+    let lock = Lockfile::load_or_default(lock_path)?;
+    let lock = packages.install(registry, manifest, &lock)?;
+    lock.save(lock_path)?;
+
+    for package in &lock.packages {
+        println!("installed {} {}", package.id()?, package.version);
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use n3_builder::inflector::Inflector;
+use n3_builder::{
+    dirs, Config, ExecRoot, GlobalVars, Result, Space, Sweep, SweepSummary, Trial, TrialStatus,
+};
+use n3_net_client::Work;
+
+/// The maximum time for a spawned work to begin, before its trial is failed.
+const START_TIMEOUT: Duration = Duration::from_secs(300);
+
+pub fn command() -> Command {
+    Command::new("sweep")
+        .about("Runs an exec many times over the spaces of the args")
+        .arg(Arg::new("exec").help("The exec to run").required(true))
+        .arg(
+            Arg::new("grid")
+                .long("grid")
+                .help("The grid space, e.g. \"batch size=32,64\" or \"model.K=3,5\"")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("random")
+                .long("random")
+                .help("The random space, e.g. \"learning rate=loguniform(1e-4,1e-2)\"")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("trials")
+                .long("trials")
+                .help("The number of trials per each point of the grid")
+                .value_parser(value_parser!(u64))
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .long("parallel")
+                .help("The maximum number of the works running at once")
+                .value_parser(value_parser!(u64))
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .help("The seed of the random spaces (default: now)")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("command")
+                .long("command")
+                .help("The command of the works")
+                .value_parser(["train", "eval"])
+                .default_value("train"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("The config file of the other args (.toml or .yaml)"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .help("The path of the sweep summary (default: in the logs)"),
+        )
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let exec = matches.get_one::<String>("exec").unwrap();
+    let command = matches.get_one::<String>("command").unwrap();
+    let parallel = (*matches.get_one::<u64>("parallel").unwrap()).max(1);
+    let seed = match matches.get_one::<u64>("seed") {
+        Some(seed) => *seed,
+        None => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+
+    // Step 1. parse the spaces
+    let grid = matches.get_many::<String>("grid").into_iter().flatten();
+    let random = matches.get_many::<String>("random").into_iter().flatten();
+    let sweep = Sweep {
+        spaces: grid
+            .map(|x| Space::parse_grid(x))
+            .chain(random.map(|x| Space::parse_random(x)))
+            .collect::<Result<_>>()?,
+        trials: *matches.get_one::<u64>("trials").unwrap() as usize,
+        seed,
+    };
+
+    let config = matches
+        .get_one::<String>("config")
+        .map(|x| Config::load(Path::new(x)))
+        .transpose()?;
+    if let Some(config) = &config {
+        config.apply_env(env)?;
+    }
+    let machines = env.get_string_list(dirs::N3_MACHINES)?;
+
+    // Step 2. build the programs ahead, to validate all the args before running
+    let mut root = ExecRoot::try_new(env.clone(), Default::default())?;
+    let mut summary = SweepSummary {
+        exec: exec.to_pascal_case(),
+        seed,
+        trials: vec![],
+    };
+    let mut programs = vec![];
+    for (index, variant) in sweep.variants().into_iter().enumerate() {
+        let mut args = root.get(&exec.to_pascal_case())?;
+        if let Some(config) = &config {
+            config.apply_args(env, &mut args)?;
+        }
+        for (name, value) in &variant {
+            // note: the dotted names are the nested node variables, e.g. `model.kernel size`
+            if name.contains('.') {
+                args.set_nested(name, value)?;
+            } else {
+                let name = args.find_name(name).unwrap_or(name).to_string();
+                args.set(&name, value)?;
+            }
+        }
+
+        let program = args.build_uncompacted_with_env()?;
+        let mut trial = Trial::new(index, variant);
+        trial.program = Some(program.digest()?);
+        summary.trials.push(trial);
+        programs.push(program.save_to_binary()?);
+    }
+
+    let output = match matches.get_one::<String>("output") {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let logs_dir = env
+                .root_dir()
+                .join(dirs::LOGS_DIR)
+                .join(exec.to_snake_case());
+            fs::create_dir_all(&logs_dir)?;
+            logs_dir.join(format!("sweep-{}.toml", seed))
+        }
+    };
+    summary.save(&output)?;
+    println!("sweep: {} trials -> {}", programs.len(), output.display());
+
+    // Step 3. run the works, with the concurrency limit
+    let mut pending: VecDeque<_> = (0..programs.len()).collect();
+    let mut running: Vec<(usize, Work, Instant)> = vec![];
+    while !pending.is_empty() || !running.is_empty() {
+        while running.len() < parallel as usize {
+            let index = match pending.pop_front() {
+                Some(index) => index,
+                None => break,
+            };
+            let trial = &mut summary.trials[index];
+            match Work::spawn(&programs[index], command, &machines) {
+                Ok(work) => {
+                    trial.status = TrialStatus::Running;
+                    trial.work = Some(work.id().to_string());
+                    println!("[trial {}] running: {:?}", index, &trial.args);
+                    running.push((index, work, Instant::now()));
+                }
+                Err(e) => {
+                    trial.status = TrialStatus::Failed;
+                    trial.error = Some(format!("{:?}", e));
+                    println!("[trial {}] failed to spawn: {:?}", index, e);
+                }
+            }
+            summary.save(&output)?;
+        }
+
+        thread::sleep(Duration::from_secs(1));
+
+        let mut still_running = vec![];
+        for (index, work, spawned) in running {
+            let trial = &mut summary.trials[index];
+            let status = match work.status() {
+                Ok(status) => status,
+                Err(e) => {
+                    trial.status = TrialStatus::Failed;
+                    trial.error = Some(format!("{:?}", e));
+                    continue;
+                }
+            };
+            trial.date_begin = status.date_begin.map(|x| x.to_rfc3339());
+            trial.date_end = status.date_end.map(|x| x.to_rfc3339());
+            trial.metrics = status.metrics;
+
+            // note: the work may not be started yet
+            let is_starting = status.date_begin.is_none() && status.error_msg.is_none();
+            let is_starting = is_starting && spawned.elapsed() < START_TIMEOUT;
+            if status.is_running || is_starting {
+                still_running.push((index, work, spawned));
+                continue;
+            }
+            trial.status = match status.error_msg {
+                Some(e) => {
+                    trial.error = Some(e);
+                    TrialStatus::Failed
+                }
+                None if status.date_begin.is_none() => {
+                    trial.error = Some(format!("not started in {:?}", START_TIMEOUT));
+                    TrialStatus::Failed
+                }
+                None => TrialStatus::Done,
+            };
+            println!("[trial {}] {:?}", index, trial.status);
+        }
+        running = still_running;
+        summary.save(&output)?;
+    }
+    Ok(())
+}
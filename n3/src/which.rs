@@ -0,0 +1,41 @@
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+
+use n3_builder::{ExecRoot, GlobalVars, Result};
+
+pub fn command() -> Command {
+    Command::new("which")
+        .about("Shows the file which each node came from")
+        .arg(
+            Arg::new("nodes")
+                .help("The nodes to show (default: all)")
+                .num_args(1..),
+        )
+}
+
+pub fn f(env: &GlobalVars, matches: &ArgMatches) -> Result<()> {
+    let root = ExecRoot::try_new(env.clone(), Default::default())?;
+    let origins = root.node_origins();
+
+    let names: Vec<_> = match matches.get_many::<String>("nodes") {
+        Some(names) => names.cloned().collect(),
+        None => origins.keys().cloned().collect(),
+    };
+
+    let mut is_missing = false;
+    for name in &names {
+        match origins.get(name) {
+            Some(path) => println!("{}\t{}", name, path),
+            None => {
+                println!("error: no such node `{}`", name);
+                is_missing = true;
+            }
+        }
+    }
+
+    if is_missing {
+        process::exit(1);
+    }
+    Ok(())
+}